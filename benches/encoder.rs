@@ -1,8 +1,13 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use dynamic_data_experiments::{byte_data::{Params,Data}, encoder::{G8Encoder, BLSEncoder}};
-use dynamic_data_experiments::encoder::BLSFieldEncoder;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use dynamic_data_experiments::{byte_data::{Params,Data}, encoder::{G8Encoder, BLSData, BLSEncoder}};
+use dynamic_data_experiments::encoder::{BLSFatEncoder, BLSFieldEncoder};
+use dynamic_data_experiments::fat_matrix::{FatMatrix, FatParams};
 use dynamic_data_experiments::field_matrix::Matrix;
-use dynamic_data_experiments::traits::{DataMatrix, Encoder};
+use dynamic_data_experiments::kzg10::{KZG10PolyComm, F};
+use dynamic_data_experiments::matrix_commit::MatrixPolyComm;
+use dynamic_data_experiments::srs::SetupSource;
+use dynamic_data_experiments::traits::{ColumnUpdate, DataMatrix, Encoder, MatrixPolyCommScheme};
+use ark_std::UniformRand;
 
 fn bench_rs_encode(c: &mut Criterion) {
     // test parameters
@@ -33,7 +38,7 @@ fn bench_bls_encode(c: &mut Criterion) {
     let params = Params { k, n, m };
 
     // generate a random data matrix once
-    let data = Data::new_random(params.clone());
+    let data = BLSData::new(Data::new_random(params.clone()));
 
     c.bench_function("BLSEncoder::encode", |b| {
         b.iter(|| {
@@ -62,5 +67,190 @@ fn bench_bls_field_encode(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_rs_encode, bench_bls_encode, bench_bls_field_encode);
+fn bench_fat_encode(c: &mut Criterion) {
+    // same number of field elements per row for every cell size
+    let k = 100;
+    let p = 100;
+    let n = k + p;
+    let elms_per_row = 256;
+
+    for cell_size in [1, 4, 16] {
+        let params = Params { k, n, m: elms_per_row / cell_size };
+        let data = FatMatrix::new_random(FatParams { params, cell_size });
+
+        c.bench_function(&format!("BLSFatEncoder::encode cell_size={}", cell_size), |b| {
+            b.iter(|| {
+                let mut d = black_box(data.clone());
+                BLSFatEncoder::encode(&mut d).expect("encode failed");
+            });
+        });
+    }
+}
+
+/// a thread pool to run the benchmark on, without the `parallel` feature everything runs on the calling thread
+struct Threads {
+    count: usize,
+    #[cfg(feature = "parallel")]
+    pool: rayon::ThreadPool,
+}
+
+impl Threads {
+    /// one thread, and all available threads with the `parallel` feature
+    fn all() -> Vec<Self> {
+        #[cfg(feature = "parallel")]
+        {
+            let max = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+            let mut counts = vec![1, max];
+            counts.dedup();
+            counts.into_iter()
+                .map(|count| Self {
+                    count,
+                    pool: rayon::ThreadPoolBuilder::new().num_threads(count).build().expect("thread pool"),
+                })
+                .collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        vec![Self { count: 1 }]
+    }
+
+    fn run<R: Send>(&self, f: impl FnOnce() -> R + Send) -> R {
+        #[cfg(feature = "parallel")]
+        return self.pool.install(f);
+        #[cfg(not(feature = "parallel"))]
+        f()
+    }
+}
+
+/// whole-matrix encoding throughput of each encoder on 1024 columns
+fn bench_large_encode(c: &mut Criterion) {
+    // 1024 columns, half of the rows are parity
+    let m = 1024;
+    let mut group = c.benchmark_group("encode_large");
+    group.sample_size(10);
+    for n in [256, 1024] {
+        let params = Params { k: n / 2, n, m };
+        group.throughput(Throughput::Elements((n * m) as u64));
+
+        // GF(2^8) Reed-Solomon is limited to 256 shards
+        if n <= 256 {
+            let data = Data::new_random(params.clone());
+            group.bench_with_input(BenchmarkId::new("G8Encoder", n), &data, |b, data| {
+                b.iter(|| {
+                    let mut d = black_box(data.clone());
+                    G8Encoder::encode(&mut d).expect("encode failed");
+                });
+            });
+        }
+
+        let data = BLSData::new(Data::new_random(params.clone()));
+        group.bench_with_input(BenchmarkId::new("BLSEncoder", n), &data, |b, data| {
+            b.iter(|| {
+                let mut d = black_box(data.clone());
+                BLSEncoder::encode(&mut d).expect("encode failed");
+            });
+        });
+
+        let matrix = Matrix::new_random(params);
+        group.bench_with_input(BenchmarkId::new("BLSFieldEncoder", n), &matrix, |b, matrix| {
+            b.iter(|| {
+                let mut d = black_box(matrix.clone());
+                BLSFieldEncoder::encode(&mut d).expect("encode failed");
+            });
+        });
+    }
+    group.finish();
+}
+
+fn bench_single_cell_write(c: &mut Criterion) {
+    // re-encoding the whole column vs adding the delta of the written cell to the parity
+    let m = 16;
+    let (row, col) = (3, 5);
+    let mut group = c.benchmark_group("single_cell_write");
+    for n in [256, 1024, 4096] {
+        let params = Params { k: n / 2, n, m };
+
+        // GF(2^8) Reed-Solomon is limited to 256 shards
+        if n <= 256 {
+            let mut data = Data::new_random(params.clone());
+            G8Encoder::encode(&mut data).expect("encode failed");
+            let old = data.get(row, col).expect("get failed");
+            data.set(row, col, !old).expect("set failed");
+            group.bench_with_input(BenchmarkId::new("G8Encoder::encode_col", n), &data, |b, data| {
+                b.iter(|| {
+                    let mut d = black_box(data.clone());
+                    G8Encoder::encode_col(&mut d, col).expect("encode col failed");
+                });
+            });
+            group.bench_with_input(BenchmarkId::new("G8Encoder::update_parity", n), &data, |b, data| {
+                b.iter(|| {
+                    let mut d = black_box(data.clone());
+                    G8Encoder::update_parity(&mut d, col, &[row], &[old]).expect("update parity failed");
+                });
+            });
+        }
+
+        let mut matrix = Matrix::new_random(params);
+        BLSFieldEncoder::encode(&mut matrix).expect("encode failed");
+        let old = matrix.get(row, col).expect("get failed");
+        matrix.set(row, col, F::rand(&mut ark_std::test_rng())).expect("set failed");
+        group.bench_with_input(BenchmarkId::new("BLSFieldEncoder::encode_col", n), &matrix, |b, matrix| {
+            b.iter(|| {
+                let mut d = black_box(matrix.clone());
+                BLSFieldEncoder::encode_col(&mut d, col).expect("encode col failed");
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("BLSFieldEncoder::update_parity", n), &matrix, |b, matrix| {
+            b.iter(|| {
+                let mut d = black_box(matrix.clone());
+                BLSFieldEncoder::update_parity(&mut d, col, &[row], &[old]).expect("update parity failed");
+            });
+        });
+    }
+    group.finish();
+}
+
+/// single vs. multi-thread throughput (cells per second) of encoding, committing and updating
+/// the commitments, at k = n/2 up to thousands of rows
+fn bench_threads(c: &mut Criterion) {
+    let m = 16;
+    let threads = Threads::all();
+    for n in [256, 1024, 4096] {
+        let k = n / 2;
+        let params = Params { k, n, m };
+        let mut data = Matrix::<F>::new_random(params.clone());
+        BLSFieldEncoder::encode(&mut data).expect("encode failed");
+
+        type C = MatrixPolyComm<F, KZG10PolyComm>;
+        let srs = C::setup(m, &SetupSource::InsecureTest).expect("setup failed");
+        let comm = C::commit(&srs, &data).expect("commit failed");
+        let rng = &mut ark_std::test_rng();
+        let updates: Vec<ColumnUpdate<F>> = (0..4)
+            .map(|col| ColumnUpdate::new(col, data.get_col(col).unwrap(), (0..n).map(|_| F::rand(rng)).collect()))
+            .collect();
+
+        let mut group = c.benchmark_group(format!("threads n={} k={} m={}", n, k, m));
+        group.sample_size(10);
+        group.throughput(Throughput::Elements((n * m) as u64));
+        for t in &threads {
+            group.bench_function(BenchmarkId::new("BLSFieldEncoder::encode", t.count), |b| {
+                b.iter(|| {
+                    let mut d = black_box(data.clone());
+                    t.run(|| BLSFieldEncoder::encode(&mut d)).expect("encode failed");
+                });
+            });
+            group.bench_function(BenchmarkId::new("MatrixPolyComm::commit", t.count), |b| {
+                b.iter(|| t.run(|| C::commit(&srs, black_box(&data))).expect("commit failed"));
+            });
+            group.bench_function(BenchmarkId::new("MatrixPolyComm::update_commitments_batch", t.count), |b| {
+                b.iter(|| {
+                    let mut comm = black_box(comm.clone());
+                    t.run(|| C::update_commitments_batch(&srs, &mut comm, &updates)).expect("update failed");
+                });
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_rs_encode, bench_bls_encode, bench_bls_field_encode, bench_fat_encode, bench_large_encode, bench_single_cell_write, bench_threads);
 criterion_main!(benches);
@@ -0,0 +1,414 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, Result};
+use ark_poly::univariate::DensePolynomial;
+use ark_ff::PrimeField;
+use ark_poly::DenseUVPolynomial;
+use ark_poly_commit::kzg10::{Commitment, Proof, Randomness, VerifierKey};
+use ark_poly_commit::{LabeledPolynomial, PCCommitmentState};
+use ark_serialize::Compress;
+use clap::{Parser, Subcommand, ValueEnum};
+use dynamic_data_experiments::byte_data::Data;
+use dynamic_data_experiments::encoder::{changed_cells, BLSFieldEncoder, G8Encoder};
+use dynamic_data_experiments::field_matrix::{packed_bytes_per_elm, Matrix};
+use dynamic_data_experiments::kzg10::{get_vk, KZG10CommitOutput, KZG10PolyComm, KZG10SRS, E, F};
+use dynamic_data_experiments::matrix_commit::MatrixPolyComm;
+use dynamic_data_experiments::protocol::{commitments_root, verify_response, verify_storage_proof, Challenge, Response};
+use dynamic_data_experiments::serialization::{self, Kind};
+use dynamic_data_experiments::srs::{decode_hex, SetupSource, SrsFormat};
+use dynamic_data_experiments::traits::{CommitOutputTrait, DataMatrix, Encoder, MatrixCommitOutput, MatrixPolyCommScheme};
+
+type P = KZG10PolyComm;
+type C = MatrixPolyComm<F, P>;
+
+/// file names inside a data directory
+const MATRIX_FILE: &str = "matrix.bin";
+const MANIFEST_FILE: &str = "manifest.bin";
+const SRS_FILE: &str = "srs.bin";
+const VK_FILE: &str = "vk.bin";
+const COMMS_FILE: &str = "comms.bin";
+const ROOT_FILE: &str = "root.bin";
+
+/// Dynamic data experiments: erasure code, commit to and prove storage of a file
+#[derive(Parser)]
+#[command(name = "dde", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// erasure code a file into a data directory
+    Encode {
+        /// file to encode
+        #[arg(long)]
+        input: PathBuf,
+        /// output data directory
+        #[arg(long)]
+        dir: PathBuf,
+        /// number of data rows
+        #[arg(short, long)]
+        k: usize,
+        /// number of data + parity rows
+        #[arg(short, long)]
+        n: usize,
+        #[arg(long, value_enum, default_value_t = Codec::Field)]
+        codec: Codec,
+    },
+    /// commit to the rows of an encoded matrix, writes the SRS, VK, row commitments and their Merkle root
+    Commit {
+        #[arg(long)]
+        dir: PathBuf,
+        /// trusted setup ceremony file
+        #[arg(long, required_unless_present = "insecure_test")]
+        srs: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = CeremonyFormat::Ethereum)]
+        srs_format: CeremonyFormat,
+        /// use a random setup with publicly known toxic waste instead of a ceremony, only for testing
+        #[arg(long, conflicts_with = "srs")]
+        insecure_test: bool,
+    },
+    /// derive a sampling challenge from a seed, or from public randomness bound to the commitments root
+    Challenge {
+        /// row commitments, used for the number of rows
+        #[arg(long)]
+        comms: PathBuf,
+        #[arg(long, required_unless_present = "randomness", conflicts_with = "randomness")]
+        seed: Option<u64>,
+        /// hex encoded public randomness, e.g. the epoch/block randomness
+        #[arg(long, requires = "nonce")]
+        randomness: Option<String>,
+        #[arg(long)]
+        nonce: Option<u64>,
+        /// number of rows to sample
+        #[arg(long)]
+        rows: usize,
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// answer a sampling challenge with the row evaluations and their opening proofs
+    Prove {
+        #[arg(long)]
+        dir: PathBuf,
+        #[arg(long)]
+        challenge: PathBuf,
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// verify a proof against the row commitments and the verifier key,
+    /// with `--randomness` the challenge is re-derived instead of read from a file
+    Verify {
+        #[arg(long)]
+        comms: PathBuf,
+        #[arg(long)]
+        vk: PathBuf,
+        #[arg(long, required_unless_present = "randomness", conflicts_with = "randomness")]
+        challenge: Option<PathBuf>,
+        #[arg(long, requires_all = ["nonce", "rows"])]
+        randomness: Option<String>,
+        #[arg(long)]
+        nonce: Option<u64>,
+        /// number of sampled rows
+        #[arg(long)]
+        rows: Option<usize>,
+        #[arg(long)]
+        proof: PathBuf,
+    },
+    /// replace the data cells of a column, re-encode it and refresh the commitments
+    Update {
+        #[arg(long)]
+        dir: PathBuf,
+        /// column index
+        #[arg(long)]
+        col: usize,
+        /// the new column data: `k` bytes for the g8 codec,
+        /// up to `k` packed field elements (31 bytes each) for the field codec
+        #[arg(long)]
+        input: PathBuf,
+    },
+}
+
+/// erasure code used for the matrix
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum Codec {
+    /// Reed-Solomon over GF(2^8) on the bytes, each byte is committed as a field element
+    G8,
+    /// Reed-Solomon over the BLS12-381 scalar field on bytes packed into field elements
+    Field,
+}
+
+/// format of the trusted setup ceremony file
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum CeremonyFormat {
+    /// `trusted_setup.txt` of the Ethereum KZG ceremony
+    Ethereum,
+    /// arkworks serialized `UniversalParams`
+    Arkworks,
+}
+
+/// the encoded matrix as stored on disk
+enum StoredMatrix {
+    G8(Data<u8>),
+    Field(Matrix<F>),
+}
+
+impl StoredMatrix {
+    fn write(&self, path: &Path) -> Result<()> {
+        match self {
+            StoredMatrix::G8(data) => serialization::write_file(path, data, Compress::Yes),
+            StoredMatrix::Field(matrix) => serialization::write_file(path, matrix, Compress::Yes),
+        }
+    }
+
+    /// the codec is given by the kind of the container
+    fn read(path: &Path) -> Result<Self> {
+        let bytes = fs::read(path)?;
+        match serialization::peek_kind(&bytes)? {
+            Kind::ByteMatrix => Ok(StoredMatrix::G8(serialization::from_bytes(&bytes)?)),
+            Kind::FieldMatrix => Ok(StoredMatrix::Field(serialization::from_bytes(&bytes)?)),
+            kind => Err(anyhow!("expected a matrix, found {:?}", kind)),
+        }
+    }
+
+    /// the field matrix that is committed to
+    fn field_matrix(&self) -> Matrix<F> {
+        match self {
+            StoredMatrix::G8(data) => Matrix::from_data(data),
+            StoredMatrix::Field(matrix) => matrix.clone(),
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    match cli.command {
+        Command::Encode { input, dir, k, n, codec } => encode(&input, &dir, k, n, codec),
+        Command::Commit { dir, srs, srs_format, insecure_test } => {
+            let source = match (srs, insecure_test) {
+                (Some(path), false) => {
+                    let format = match srs_format {
+                        CeremonyFormat::Ethereum => SrsFormat::EthereumCeremony,
+                        CeremonyFormat::Arkworks => SrsFormat::Arkworks,
+                    };
+                    SetupSource::File { path, format }
+                }
+                (None, true) => SetupSource::InsecureTest,
+                (Some(_), true) => return Err(anyhow!("--srs and --insecure-test can't be used together")),
+                (None, false) => return Err(anyhow!("either --srs or --insecure-test is needed")),
+            };
+            commit(&dir, &source)
+        }
+        Command::Challenge { comms, seed, randomness, nonce, rows, out } => {
+            let public = randomness.map(|r| (r, nonce.unwrap_or_default()));
+            challenge(&comms, seed, public, rows, &out)
+        }
+        Command::Prove { dir, challenge, out } => prove(&dir, &challenge, &out),
+        Command::Verify { comms, vk, challenge, randomness, nonce, rows, proof } => {
+            let source = match (challenge, randomness) {
+                (Some(path), _) => ChallengeSource::File(path),
+                (None, Some(randomness)) => ChallengeSource::Public {
+                    randomness,
+                    nonce: nonce.unwrap_or_default(),
+                    rows: rows.unwrap_or_default(),
+                },
+                (None, None) => return Err(anyhow!("either a challenge or public randomness is needed")),
+            };
+            if verify(&comms, &vk, &source, &proof)? {
+                println!("proof OK");
+                Ok(())
+            } else {
+                Err(anyhow!("proof rejected"))
+            }
+        }
+        Command::Update { dir, col, input } => update(&dir, col, &input),
+    }
+}
+
+fn encode(input: &Path, dir: &Path, k: usize, n: usize, codec: Codec) -> Result<()> {
+    let (data, manifest) = Data::from_reader(fs::File::open(input)?, k, n)?;
+    let stored = match codec {
+        Codec::G8 => {
+            let mut data = data;
+            G8Encoder::encode(&mut data)?;
+            StoredMatrix::G8(data)
+        }
+        Codec::Field => {
+            let mut matrix = Matrix::from_data_packed(&data);
+            BLSFieldEncoder::encode(&mut matrix)?;
+            StoredMatrix::Field(matrix)
+        }
+    };
+
+    fs::create_dir_all(dir)?;
+    stored.write(&dir.join(MATRIX_FILE))?;
+    serialization::write_file(&dir.join(MANIFEST_FILE), &manifest, Compress::Yes)?;
+    println!("encoded {} bytes into {} rows ({} data)", manifest.original_len, n, k);
+    Ok(())
+}
+
+fn commit(dir: &Path, source: &SetupSource) -> Result<()> {
+    let matrix = StoredMatrix::read(&dir.join(MATRIX_FILE))?.field_matrix();
+    let srs = C::setup(matrix.params.m, source)?;
+    let comm = C::commit(&srs, &matrix)?;
+
+    serialization::write_file(&dir.join(SRS_FILE), &srs, Compress::Yes)?;
+    serialization::write_file(&dir.join(VK_FILE), &get_vk(&srs)?, Compress::Yes)?;
+    write_comms(dir, &comm)
+}
+
+/// where `verify` gets the challenge from
+enum ChallengeSource {
+    File(PathBuf),
+    /// derived from the commitments root and the public randomness
+    Public { randomness: String, nonce: u64, rows: usize },
+}
+
+fn challenge(comms: &Path, seed: Option<u64>, public: Option<(String, u64)>, rows: usize, out: &Path) -> Result<()> {
+    let comms: Vec<Commitment<E>> = serialization::read_file(comms)?;
+    let challenge = match (seed, public) {
+        (_, Some((randomness, nonce))) => {
+            Challenge::derive(&commitments_root(&comms)?, &decode_hex(&randomness)?, nonce, comms.len(), rows)?
+        }
+        (Some(seed), None) => Challenge::from_seed(seed, comms.len(), rows)?,
+        (None, None) => return Err(anyhow!("either a seed or public randomness is needed")),
+    };
+    serialization::write_file(out, &challenge, Compress::Yes)?;
+    println!("challenge rows: {:?}", challenge.rows);
+    Ok(())
+}
+
+fn prove(dir: &Path, challenge: &Path, out: &Path) -> Result<()> {
+    let matrix = StoredMatrix::read(&dir.join(MATRIX_FILE))?.field_matrix();
+    let srs: KZG10SRS = serialization::read_file(&dir.join(SRS_FILE))?;
+    let comms: Vec<Commitment<E>> = serialization::read_file(&dir.join(COMMS_FILE))?;
+    let comm = commit_output(&matrix, comms)?;
+    let Challenge { rows, point, .. } = serialization::read_file(challenge)?;
+
+    let mut values = Vec::with_capacity(rows.len());
+    let mut proofs = Vec::with_capacity(rows.len());
+    for &row in &rows {
+        matrix.params.check_rows(row)?;
+        values.push(comm.get_poly(row).evaluate(&point));
+        proofs.push(C::open(&comm, &srs, row, point)?);
+    }
+    write_response(out, &Response { values, proofs })?;
+    println!("proved {} rows", rows.len());
+    Ok(())
+}
+
+fn verify(comms: &Path, vk: &Path, source: &ChallengeSource, proof: &Path) -> Result<bool> {
+    let comms: Vec<Commitment<E>> = serialization::read_file(comms)?;
+    let vk: VerifierKey<E> = serialization::read_file(vk)?;
+    let response = read_response(proof)?;
+
+    match source {
+        ChallengeSource::File(path) => {
+            let challenge: Challenge = serialization::read_file(path)?;
+            verify_response(&vk, &comms, &challenge, &response)
+        }
+        ChallengeSource::Public { randomness, nonce, rows } => {
+            verify_storage_proof(&vk, &comms, &decode_hex(randomness)?, *nonce, *rows, &response)
+        }
+    }
+}
+
+fn update(dir: &Path, col: usize, input: &Path) -> Result<()> {
+    let mut stored = StoredMatrix::read(&dir.join(MATRIX_FILE))?;
+    let srs: KZG10SRS = serialization::read_file(&dir.join(SRS_FILE))?;
+    let comms: Vec<Commitment<E>> = serialization::read_file(&dir.join(COMMS_FILE))?;
+    let bytes = fs::read(input)?;
+
+    let old_matrix = stored.field_matrix();
+    old_matrix.params.check_cols(col)?;
+    let mut comm = commit_output(&old_matrix, comms)?;
+    let k = old_matrix.params.k;
+
+    match &mut stored {
+        StoredMatrix::G8(data) => {
+            if bytes.len() > k {
+                return Err(anyhow!("column data has {} bytes, at most k = {} allowed", bytes.len(), k));
+            }
+            let mut new_col = bytes;
+            new_col.resize(k, 0);
+            let old_col = data.get_col(col)?;
+            data.update_col(col, &new_col)?;
+            let (changed_rows, old_vals) = changed_cells(&old_col, &new_col);
+            G8Encoder::update_parity(data, col, &changed_rows, &old_vals)?;
+        }
+        StoredMatrix::Field(matrix) => {
+            let bytes_per_elm = packed_bytes_per_elm::<F>();
+            if bytes.len() > k * bytes_per_elm {
+                return Err(anyhow!("column data has {} bytes, at most {} allowed", bytes.len(), k * bytes_per_elm));
+            }
+            let mut new_col: Vec<F> = bytes
+                .chunks(bytes_per_elm)
+                .map(F::from_le_bytes_mod_order)
+                .collect();
+            new_col.resize(k, F::from(0u8));
+            let old_col = matrix.get_col(col)?;
+            matrix.update_col(col, &new_col)?;
+            let (changed_rows, old_vals) = changed_cells(&old_col, &new_col);
+            BLSFieldEncoder::update_parity(matrix, col, &changed_rows, &old_vals)?;
+        }
+    }
+
+    let old_col = old_matrix.get_col(col)?;
+    let new_col = stored.field_matrix().get_col(col)?;
+    C::update_commitments(&srs, &mut comm, col, &old_col, &new_col)?;
+
+    stored.write(&dir.join(MATRIX_FILE))?;
+    write_comms(dir, &comm)
+}
+
+// --------------- Utils -----------------
+
+/// rebuild the commitment output of the prover from the matrix rows and the stored commitments
+fn commit_output(matrix: &Matrix<F>, comms: Vec<Commitment<E>>) -> Result<MatrixCommitOutput<F, P>> {
+    if comms.len() != matrix.params.n {
+        return Err(anyhow!("{} commitments for {} rows", comms.len(), matrix.params.n));
+    }
+    let outputs = matrix.elms.iter().zip(comms).map(|(row, comm)| {
+        let poly = LabeledPolynomial::new(
+            String::from("row_poly"),
+            DensePolynomial::from_coefficients_vec(row.clone()),
+            Some(row.len()),
+            None,
+        );
+        KZG10CommitOutput::new(poly, comm, Randomness::empty())
+    }).collect();
+    Ok(MatrixCommitOutput::new(outputs))
+}
+
+/// write the row commitments and their Merkle root
+fn write_comms(dir: &Path, comm: &MatrixCommitOutput<F, P>) -> Result<()> {
+    let comms: Vec<Commitment<E>> = comm.comm_output.iter().map(|c| *c.get_comm()).collect();
+    let root = commitments_root(&comms)?;
+
+    serialization::write_file(&dir.join(COMMS_FILE), &comms, Compress::Yes)?;
+    fs::write(dir.join(ROOT_FILE), root)?;
+    println!("committed {} rows, root: {}", comms.len(), to_hex(&root));
+    Ok(())
+}
+
+/// a response file holds the evaluations container followed by the proofs container
+fn write_response(path: &Path, response: &Response) -> Result<()> {
+    let mut file = fs::File::create(path)?;
+    serialization::to_writer(&response.values, Compress::Yes, &mut file)?;
+    serialization::to_writer(&response.proofs, Compress::Yes, &mut file)
+}
+
+fn read_response(path: &Path) -> Result<Response> {
+    let bytes = fs::read(path)?;
+    let mut reader = bytes.as_slice();
+    let values: Vec<F> = serialization::from_reader(&mut reader)?;
+    let proofs: Vec<Proof<E>> = serialization::from_reader(&mut reader)?;
+    if !reader.is_empty() {
+        return Err(anyhow!("{} trailing bytes after the proofs", reader.len()));
+    }
+    Ok(Response { values, proofs })
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
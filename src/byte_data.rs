@@ -1,3 +1,5 @@
+use std::io::Read;
+use anyhow::{anyhow, Result};
 use rand::Rng;
 use crate::traits::DataMatrix;
 
@@ -13,6 +15,24 @@ pub struct Params{
 }
 
 impl Params{
+    /// params for `len` bytes of data split over `k` data rows, the number of columns is
+    /// the smallest `m` such that the `k * m` data cells can hold all bytes (at least 1)
+    pub fn for_len(len: usize, k: usize, n: usize) -> Result<Params>{
+        if k == 0 || n < k {
+            return Err(anyhow!("invalid code params k = {}, n = {}", k, n));
+        }
+        Ok(Params{
+            k,
+            n,
+            m: len.div_ceil(k).max(1),
+        })
+    }
+
+    /// number of data bytes the `k` data rows can hold
+    pub fn capacity(&self) -> usize {
+        self.k * self.m
+    }
+
     pub fn check_bounds(&self, r: usize, c: usize) -> anyhow::Result<()>{
         assert!(
             r < self.n,
@@ -49,6 +69,17 @@ impl Params{
     }
 }
 
+/// a matrix can be truncated to `m` columns if it keeps at least one and doesn't grow
+pub fn check_truncate(params: &Params, m: usize) -> Result<()> {
+    if m == 0 {
+        return Err(anyhow!("a matrix must keep at least one column"));
+    }
+    if m > params.m {
+        return Err(anyhow!("can't truncate {} columns to {}", params.m, m));
+    }
+    Ok(())
+}
+
 /// data struct contains shards matrix with dimensions `n`*`m`
 /// the matrix contains n rows, k of which are source data and the rest p = (n-k) are parity
 #[derive(Clone, Debug)]
@@ -57,6 +88,77 @@ pub struct Data<T>{
     pub matrix: Vec<Vec<T>>,
 }
 
+/// metadata needed to turn a (reconstructed) data matrix back into the original bytes
+#[derive(Clone, Debug)]
+pub struct Manifest{
+    pub params: Params,
+    /// length of the original data before padding
+    pub original_len: usize,
+}
+
+/// the data bytes are laid out column-major over the `k` data rows,
+/// i.e. byte `i` goes to row `i % k` of column `i / k`, so that each column holds a contiguous
+/// range of the original bytes. The tail of the last column(s) is zero padded.
+impl Data<u8>{
+
+    /// build the data matrix from `bytes`, choosing the number of columns from the length
+    pub fn from_bytes(bytes: &[u8], k: usize, n: usize) -> Result<(Self, Manifest)>{
+        let params = Params::for_len(bytes.len(), k, n)?;
+        Self::from_bytes_with_params(bytes, params)
+    }
+
+    /// build the data matrix from `bytes` with the given params, fails if the data doesn't fit
+    pub fn from_bytes_with_params(bytes: &[u8], params: Params) -> Result<(Self, Manifest)>{
+        if bytes.len() > params.capacity() {
+            return Err(anyhow!(
+                "{} bytes don't fit in {} data rows of {} columns",
+                bytes.len(),
+                params.k,
+                params.m
+            ));
+        }
+
+        let mut matrix = vec![vec![0u8; params.m]; params.n];
+        for (i, b) in bytes.iter().enumerate() {
+            matrix[i % params.k][i / params.k] = *b;
+        }
+
+        let manifest = Manifest{
+            params: params.clone(),
+            original_len: bytes.len(),
+        };
+        Ok((Self{ params, matrix }, manifest))
+    }
+
+    /// read the whole stream and build the data matrix from it
+    pub fn from_reader<R: Read>(mut reader: R, k: usize, n: usize) -> Result<(Self, Manifest)>{
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes, k, n)
+    }
+
+    /// the original bytes, read from the data rows and stripped of the padding
+    pub fn into_bytes(self, manifest: &Manifest) -> Result<Vec<u8>>{
+        let params = &manifest.params;
+        if self.params.k != params.k || self.params.m != params.m {
+            return Err(anyhow!(
+                "data matrix shape ({}x{}) doesn't match the manifest ({}x{})",
+                self.params.k,
+                self.params.m,
+                params.k,
+                params.m
+            ));
+        }
+        if manifest.original_len > params.capacity() {
+            return Err(anyhow!("manifest length {} exceeds the matrix capacity", manifest.original_len));
+        }
+
+        Ok((0..manifest.original_len)
+            .map(|i| self.matrix[i % params.k][i / params.k])
+            .collect())
+    }
+}
+
 impl<T> Data<T>{
 
     pub fn get_row_mut(&mut self, idx: usize) -> &mut Vec<T>{
@@ -97,7 +199,7 @@ impl DataMatrix<u8> for Data<u8> {
 
     fn get(&self, r: usize, c: usize) -> anyhow::Result<u8> {
         self.params.check_bounds(r,c)?;
-        Ok(self.matrix[r][c].clone())
+        Ok(self.matrix[r][c])
     }
 
     fn get_row(&self, r: usize) -> anyhow::Result<Vec<u8>> {
@@ -111,7 +213,7 @@ impl DataMatrix<u8> for Data<u8> {
 
         Ok(self.matrix
             .iter()
-            .map(|row| row[c].clone())
+            .map(|row| row[c])
             .collect())
     }
 
@@ -134,9 +236,48 @@ impl DataMatrix<u8> for Data<u8> {
         self.params.check_cols(c)?;
 
         // write into each of the k data row at position c
-        for i in 0..self.params.k {
-            self.matrix[i][c] = new_col[i];
+        for (i, val) in new_col.iter().enumerate() {
+            self.matrix[i][c] = *val;
+        }
+        Ok(())
+    }
+
+    fn append_cols(&mut self, new_cols: &[Vec<u8>]) -> anyhow::Result<()> {
+        for col in new_cols {
+            assert!(
+                col.len() == self.params.k,
+                "new_col length ({}) must equal k ({})",
+                col.len(),
+                self.params.k
+            );
+        }
+        for (r, row) in self.matrix.iter_mut().enumerate() {
+            if r < self.params.k {
+                row.extend(new_cols.iter().map(|col| col[r]));
+            } else {
+                row.resize(row.len() + new_cols.len(), 0u8);
+            }
+        }
+        self.params.m += new_cols.len();
+        Ok(())
+    }
+
+    fn truncate_cols(&mut self, m: usize) -> anyhow::Result<()> {
+        check_truncate(&self.params, m)?;
+        for row in self.matrix.iter_mut() {
+            row.truncate(m);
+        }
+        self.params.m = m;
+        Ok(())
+    }
+
+    fn delete_col(&mut self, c: usize) -> anyhow::Result<()> {
+        self.params.check_cols(c)?;
+        check_truncate(&self.params, self.params.m - 1)?;
+        for row in self.matrix.iter_mut() {
+            row.remove(c);
         }
+        self.params.m -= 1;
         Ok(())
     }
 
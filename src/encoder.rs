@@ -2,15 +2,18 @@ use std::marker::PhantomData;
 use anyhow::{anyhow, Result};
 use ark_bls12_381::Bls12_381;
 use ark_ec::pairing::Pairing;
-use ark_ff::PrimeField;
-use ark_poly::{DenseUVPolynomial, GeneralEvaluationDomain, Polynomial};
+use ark_ff::{batch_inversion, BigInteger, Field, One, PrimeField, Zero};
+use ark_poly::{DenseUVPolynomial, GeneralEvaluationDomain};
 use ark_poly::univariate::DensePolynomial;
+use ark_serialize::CanonicalDeserialize;
+use ark_std::{cfg_into_iter, cfg_iter};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use reed_solomon_erasure::galois_8::ReedSolomon;
 use crate::byte_data::{Data, Params};
 use crate::traits::{DataMatrix, Encoder};
 use ark_poly::domain::EvaluationDomain;
-// use ark_poly_commit::Evaluations;
-use ark_poly::Evaluations;
+use crate::fat_matrix::FatMatrix;
 use crate::field_matrix::Matrix;
 
 
@@ -20,10 +23,16 @@ pub struct G8Encoder<T>{
     phantom_data: PhantomData<T>
 }
 
+impl Default for G8Encoder<u8> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl G8Encoder<u8>{
     pub fn new() -> Self{
         Self{
-            phantom_data: PhantomData::default()
+            phantom_data: PhantomData
         }
     }
 }
@@ -85,16 +94,35 @@ impl Encoder<u8> for G8Encoder<u8> {
         rse.encode(&mut refs)?;
 
         // Write back parity
-        for i in 0..n {
-            let b = refs[i][0];
-            if i >= k {
-                data.set(i,c, b)?;
-            }
+        for (i, shard) in refs.iter().enumerate().skip(k) {
+            data.set(i,c, shard[0])?;
         }
 
         Ok(())
     }
 
+    /// addition in GF(2^8) is xor, so each changed row xors the encoding of its delta into the parity
+    fn update_parity(data: &mut Data<u8>, c: usize, changed_rows: &[usize], old_vals: &[u8]) -> Result<()> {
+        check_changed_rows(&data.params, c, changed_rows, old_vals.len())?;
+        let n = data.params.n;
+        let k = data.params.k;
+        let rse = ReedSolomon::new(k, n - k)?;
+
+        for (&r, old) in changed_rows.iter().zip(old_vals) {
+            let delta = data.get(r, c)? ^ old;
+            if delta == 0 {
+                continue;
+            }
+            let mut parity_delta = vec![[0u8]; n - k];
+            rse.encode_single_sep(r, &[delta], &mut parity_delta)?;
+            for (i, d) in (k..n).zip(parity_delta) {
+                let byte = data.get(i, c)?;
+                data.set(i, c, byte ^ d[0])?;
+            }
+        }
+        Ok(())
+    }
+
     fn reconstruct(params: Params, matrix_opts: &mut Vec<Option<Vec<u8>>>) -> Result<()>{
         let n = params.n;
         let k = params.k;
@@ -104,6 +132,15 @@ impl Encoder<u8> for G8Encoder<u8> {
         rse.reconstruct(matrix_opts)?;
         Ok(())
     }
+
+    fn is_codeword(params: Params, col: &[u8]) -> Result<bool> {
+        if col.len() != params.n {
+            return Ok(false);
+        }
+        let rse = ReedSolomon::new(params.k, params.n - params.k)?;
+        let shards: Vec<[u8; 1]> = col.iter().map(|b| [*b]).collect();
+        Ok(rse.verify(&shards)?)
+    }
 }
 
 // ---------------- Bls12_381 Encoder -----------------
@@ -116,44 +153,303 @@ pub struct BLSEncoder<T>{
     phantom_data: PhantomData<T>
 }
 
+impl Default for BLSEncoder<u8> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BLSEncoder<u8>{
     pub fn new() -> Self{
         Self{
-            phantom_data: PhantomData::default()
+            phantom_data: PhantomData
         }
     }
 }
 
+/// number of bytes of a serialized parity cell of the [`BLSEncoder`],
+/// i.e. the size of a serialized field element
+pub const FIELD_CELL_BYTES: usize = 32;
+
+/// byte data encoded with the [`BLSEncoder`].
+/// the parity cells are full field elements, so they are kept in a typed side matrix
+/// and the byte matrix keeps its `n`x`m` shape, its parity rows are not used.
+#[derive(Clone, Debug)]
+pub struct BLSData {
+    pub data: Data<u8>,
+    /// the `n - k` parity rows of `m` field elements each
+    pub parity: Vec<Vec<F>>,
+}
+
+impl BLSData {
+    /// the parity cells are zero until encoded
+    pub fn new(data: Data<u8>) -> Self {
+        let parity = vec![vec![F::zero(); data.params.m]; data.params.n - data.params.k];
+        Self{
+            data,
+            parity,
+        }
+    }
+
+    pub fn params(&self) -> &Params {
+        &self.data.params
+    }
+
+    /// column `c` over the field: the `k` data cells followed by the `n - k` parity cells
+    pub fn get_col(&self, c: usize) -> Result<Vec<F>> {
+        self.data.params.check_cols(c)?;
+        let data_cells = self.data.matrix[0..self.data.params.k].iter().map(|row| F::from(row[c]));
+        let parity_cells = self.parity.iter().map(|row| row[c]);
+        Ok(data_cells.chain(parity_cells).collect())
+    }
+
+    /// the rows as stored by a provider, i.e. the shards given to [`BLSEncoder::reconstruct`]:
+    /// the data rows are bytes and each parity row holds `m` cells of `FIELD_CELL_BYTES` little-endian bytes
+    pub fn to_rows(&self) -> Vec<Vec<u8>> {
+        let data_rows = self.data.matrix[0..self.data.params.k].iter().cloned();
+        let parity_rows = self.parity.iter().map(|row| row.iter().flat_map(|v| field_to_bytes(*v)).collect());
+        data_rows.chain(parity_rows).collect()
+    }
+}
+
+/// The BLS encoder over byte data works over the field `F`:
+/// the data cells are bytes (embedded in `F`) but the parity cells are full field elements,
+/// kept in the parity side matrix of [`BLSData`].
 impl Encoder<u8> for BLSEncoder<u8> {
     type Params = Params;
-    type DataMatrix<T> = Data<u8>;
+    type DataMatrix<T> = BLSData;
 
-    fn encode(data: &mut Self::DataMatrix<u8>) -> Result<()> {
-        for i in 0..data.params.m {
-            let _col = Self::encode_col(data, i)?;
+    /// all columns are encoded as one batch sharing the domain
+    fn encode(data: &mut BLSData) -> Result<()> {
+        let (k, n, m) = (data.params().k, data.params().n, data.params().m);
+        let data_cols: Vec<Vec<F>> = (0..m)
+            .map(|c| data.data.matrix[0..k].iter().map(|row| F::from(row[c])).collect())
+            .collect();
+        let parities = ColumnDomain::new(k, n)?.parity_batch(&data_cols)?;
+        data.parity = (0..n - k)
+            .map(|i| parities.iter().map(|parity| parity[i]).collect())
+            .collect();
+        Ok(())
+    }
+
+    fn encode_col(data: &mut BLSData, c: usize) -> Result<()> {
+        data.params().check_cols(c)?;
+        let n = data.params().n;
+        let k = data.params().k;
+
+        let col_f: Vec<F> = data.data.matrix[0..k].iter().map(|row| F::from(row[c])).collect();
+        let parity = ColumnDomain::new(k, n)?.parity(&col_f)?;
+        for (row, eval) in data.parity.iter_mut().zip(parity) {
+            row[c] = eval;
         }
         Ok(())
     }
 
-    fn encode_col(data: &mut Self::DataMatrix<u8>, c: usize) -> Result<()> {
-        let n = data.params.n.clone();
-        let k = data.params.k.clone();
-        let mut col = data.get_col_mut(c);
-        let col_f: Vec<F> = col.iter().map(|i| <F as PrimeField>::from_le_bytes_mod_order(&i.clone().to_le_bytes())).collect();
-        let poly_poly = UniPoly381::from_coefficients_slice(&col_f);
-        let domain: GeneralEvaluationDomain<F> = EvaluationDomain::<F>::new(n).unwrap();
+    /// the data deltas are lifted to the field and added to the parity cells
+    fn update_parity(data: &mut BLSData, c: usize, changed_rows: &[usize], old_vals: &[u8]) -> Result<()> {
+        check_changed_rows(data.params(), c, changed_rows, old_vals.len())?;
+        let n = data.params().n;
+        let k = data.params().k;
+
+        let deltas = changed_rows.iter().zip(old_vals)
+            .map(|(&r, old)| Ok((r, F::from(data.data.get(r, c)?) - F::from(*old))))
+            .collect::<Result<Vec<_>>>()?;
+        let parity_delta = ColumnDomain::new(k, n)?.parity_delta(&deltas)?;
+        for (row, d) in data.parity.iter_mut().zip(parity_delta) {
+            row[c] += d;
+        }
+        Ok(())
+    }
 
-        // let mut new_col: Vec<u8> = vec![];
-        for i in k..n{
-            let eval = poly_poly.evaluate(&domain.element(i));
-            *col[i] = eval.0.0[0] as u8;
+    /// reconstruct the missing rows in place, the rows are laid out as in [`BLSData::to_rows`]:
+    /// data rows are recovered as bytes and parity rows as serialized field elements
+    fn reconstruct(params: Params, matrix_opts: &mut Vec<Option<Vec<u8>>>) -> Result<()> {
+        let k = params.k;
+        let m = params.m;
+
+        // lift the surviving rows to the field
+        let mut field_opts: Vec<Option<Vec<F>>> = Vec::with_capacity(matrix_opts.len());
+        for (i, row) in matrix_opts.iter().enumerate() {
+            let row_f = match row {
+                None => None,
+                Some(row) if i < k => Some(row.iter().map(|b| F::from(*b)).collect()),
+                Some(row) => {
+                    if row.len() != m * FIELD_CELL_BYTES {
+                        return Err(anyhow!("parity row {} has length {}, expected {}", i, row.len(), m * FIELD_CELL_BYTES));
+                    }
+                    let cells = row.chunks(FIELD_CELL_BYTES)
+                        .map(field_from_bytes)
+                        .collect::<Result<Vec<F>>>()
+                        .map_err(|e| anyhow!("parity row {}: {}", i, e))?;
+                    Some(cells)
+                }
+            };
+            field_opts.push(row_f);
         }
 
+        BLSFieldEncoder::reconstruct(params, &mut field_opts)?;
+
+        // write back the missing rows
+        for (i, (row, row_f)) in matrix_opts.iter_mut().zip(field_opts).enumerate() {
+            if row.is_some() {
+                continue;
+            }
+            let row_f = row_f.ok_or(anyhow!("row {} was not reconstructed", i))?;
+            let bytes = if i < k {
+                row_f.iter()
+                    .map(|v| field_to_byte(*v).ok_or(anyhow!("reconstructed data cell in row {} is not a byte", i)))
+                    .collect::<Result<Vec<u8>>>()?
+            } else {
+                row_f.into_iter().flat_map(field_to_bytes).collect()
+            };
+            *row = Some(bytes);
+        }
         Ok(())
     }
 
-    fn reconstruct(_params: Params, _matrix_opts: &mut Vec<Option<Vec<u8>>>) -> Result<()> {
-        todo!()
+    /// the column is given as its `k` data bytes followed by the `n - k` serialized parity cells
+    fn is_codeword(params: Params, col: &[u8]) -> Result<bool> {
+        let k = params.k;
+        if col.len() != k + (params.n - k) * FIELD_CELL_BYTES {
+            return Ok(false);
+        }
+        let data_col: Vec<F> = col[0..k].iter().map(|b| F::from(*b)).collect();
+        let parity = ColumnDomain::new(k, params.n)?.parity(&data_col)?;
+        Ok(parity.into_iter().flat_map(field_to_bytes).eq(col[k..].iter().copied()))
+    }
+}
+
+// --------- Column evaluation domain ----------------
+
+/// The evaluation points of the rows of an encoded column.
+/// The column polynomial is interpolated over the size-`k` subdomain `H` (of size `K >= k`),
+/// data row `i` is the evaluation at `H[i]` so the code is systematic, the `K - k` remaining points
+/// of `H` are virtual rows fixed to zero. The parity rows are evaluated at the points of the
+/// extended domain `D` (with `H` a subgroup of `D`) outside `H`, taken coset by coset:
+/// parity row `k + t` is the point `g^s * H[j]` with `s = 1 + t / K` and `j = t % K`.
+#[derive(Clone, Debug)]
+pub struct ColumnDomain {
+    pub k: usize,
+    pub n: usize,
+    /// interpolation subdomain `H`
+    pub data_domain: GeneralEvaluationDomain<F>,
+    /// extension domain `D`
+    pub ext_domain: GeneralEvaluationDomain<F>,
+}
+
+impl ColumnDomain {
+    pub fn new(k: usize, n: usize) -> Result<Self> {
+        if k == 0 || k > n {
+            return Err(anyhow!("invalid code parameters: k = {}, n = {}", k, n));
+        }
+        let data_domain: GeneralEvaluationDomain<F> = EvaluationDomain::<F>::new(k).ok_or(anyhow!("polycommit domain error"))?;
+        // the extension must have at least n - k points outside of H
+        let ext_domain: GeneralEvaluationDomain<F> = EvaluationDomain::<F>::new(data_domain.size() + n - k).ok_or(anyhow!("polycommit domain error"))?;
+        Ok(Self{
+            k,
+            n,
+            data_domain,
+            ext_domain,
+        })
+    }
+
+    /// the evaluation point of row `r`
+    pub fn row_point(&self, r: usize) -> F {
+        if r < self.k {
+            self.data_domain.element(r)
+        } else {
+            let t = r - self.k;
+            let size = self.data_domain.size();
+            self.ext_domain.element(1 + t / size) * self.data_domain.element(t % size)
+        }
+    }
+
+    /// the points of the virtual zero rows
+    fn virtual_points(&self) -> Vec<F> {
+        (self.k..self.data_domain.size()).map(|j| self.data_domain.element(j)).collect()
+    }
+
+    /// the column polynomial interpolating the `k` data cells (and the virtual zero rows)
+    pub fn interpolate(&self, data_col: &[F]) -> Result<DensePolynomial<F>> {
+        if data_col.len() != self.k {
+            return Err(anyhow!("data column length ({}) must equal k ({})", data_col.len(), self.k));
+        }
+        let mut evals = data_col.to_vec();
+        evals.resize(self.data_domain.size(), F::zero());
+        Ok(DensePolynomial::from_coefficients_vec(self.data_domain.ifft(&evals)))
+    }
+
+    /// the index in `D` of the point of row `r`: `H` is the subgroup of `D` generated by `g^(|D|/K)`,
+    /// so `g^s * H[j]` is the element `s + j * |D|/K` of `D`
+    pub fn ext_index(&self, r: usize) -> usize {
+        let size = self.data_domain.size();
+        let stride = self.ext_domain.size() / size;
+        if r < self.k {
+            r * stride
+        } else {
+            let t = r - self.k;
+            1 + t / size + (t % size) * stride
+        }
+    }
+
+    /// compute the `n - k` parity cells of a column from its `k` data cells,
+    /// an inverse FFT over `H` gives the column polynomial and an FFT over `D` evaluates it at all parity points
+    pub fn parity(&self, data_col: &[F]) -> Result<Vec<F>> {
+        let poly = self.interpolate(data_col)?;
+        let evals = self.ext_domain.fft(&poly.coeffs);
+        Ok((self.k..self.n).map(|r| evals[self.ext_index(r)]).collect())
+    }
+
+    /// the change of the parity cells when data cells change by `deltas`, given as `(row, delta)`.
+    /// data row `i` adds `delta * L_i(x)` to the parity at point `x`, with `L_i` the Lagrange basis of `H`,
+    /// so the cost is `O(|deltas| * (n - k))` after computing the basis once, instead of re-encoding the column
+    pub fn parity_delta(&self, deltas: &[(usize, F)]) -> Result<Vec<F>> {
+        let mut parity_delta = vec![F::zero(); self.n - self.k];
+        if let Some(&(i, _)) = deltas.iter().find(|(i, _)| *i >= self.k) {
+            return Err(anyhow!("row {} is not a data row (k = {})", i, self.k));
+        }
+        if deltas.iter().all(|(_, delta)| delta.is_zero()) {
+            return Ok(parity_delta);
+        }
+
+        // L_i(g^s w^j) = L_0(g^s w^(j - i)), so every data row shifts the values of L_0 within each coset
+        let size = self.data_domain.size();
+        let basis = self.coset_basis((self.n - self.k).div_ceil(size));
+        for &(i, delta) in deltas {
+            for (t, cell) in parity_delta.iter_mut().enumerate() {
+                let coset = &basis[(t / size) * size..];
+                *cell += delta * coset[(t % size + size - i) % size];
+            }
+        }
+        Ok(parity_delta)
+    }
+
+    /// `L_0(g^s w^j) = (g^(sK) - 1) / (K (g^s w^j - 1))` for the cosets `s = 1..=num_cosets` and `j < K`
+    fn coset_basis(&self, num_cosets: usize) -> Vec<F> {
+        let size = self.data_domain.size();
+        let w = self.data_domain.group_gen();
+        let mut numerators = Vec::with_capacity(num_cosets);
+        let mut basis = Vec::with_capacity(num_cosets * size);
+        for s in 1..=num_cosets {
+            let g_s = self.ext_domain.element(s);
+            numerators.push((g_s.pow([size as u64]) - F::one()) * self.data_domain.size_inv());
+            // the cosets are outside of H, so the denominators are non-zero
+            let mut x = g_s;
+            for _ in 0..size {
+                basis.push(x - F::one());
+                x *= w;
+            }
+        }
+        batch_inversion(&mut basis);
+        for (coset, numerator) in basis.chunks_mut(size).zip(numerators) {
+            coset.iter_mut().for_each(|b| *b *= numerator);
+        }
+        basis
+    }
+
+    /// the parity cells of several columns, in parallel with the `parallel` feature
+    pub fn parity_batch(&self, data_cols: &[Vec<F>]) -> Result<Vec<Vec<F>>> {
+        cfg_iter!(data_cols).map(|col| self.parity(col)).collect()
     }
 }
 
@@ -167,30 +463,308 @@ impl Encoder<F> for BLSFieldEncoder<F>{
     type Params = Params;
     type DataMatrix<T> = Matrix<F>;
 
+    /// all columns are encoded as one batch sharing the domain, in parallel with the `parallel` feature
     fn encode(data: &mut Matrix<F>) -> Result<()> {
-        for i in 0..data.params.m {
-            let _col = Self::encode_col(data, i)?;
+        let (k, n) = (data.params.k, data.params.n);
+        let data_cols: Vec<Vec<F>> = (0..data.params.m)
+            .map(|c| data.elms[0..k].iter().map(|row| row[c]).collect())
+            .collect();
+        let parities = ColumnDomain::new(k, n)?.parity_batch(&data_cols)?;
+        for (c, parity) in parities.into_iter().enumerate() {
+            for (i, eval) in (k..n).zip(parity) {
+                data.set(i, c, eval)?;
+            }
         }
         Ok(())
     }
 
+    /// encode a single column in place.
+    /// row `i` of the column is the evaluation of the column polynomial at `ColumnDomain::row_point(i)`,
+    /// the polynomial is the one interpolating the `k` data cells so the code is systematic.
     fn encode_col(data: &mut Matrix<F>, c: usize) -> Result<()> {
-        let n = data.params.n.clone();
-        let k = data.params.k.clone();
+        let n = data.params.n;
+        let k = data.params.k;
         let col: Vec<F> = data.get_col(c)?;
 
-        let poly_domain: GeneralEvaluationDomain<F> = EvaluationDomain::<F>::new(n).ok_or(anyhow!("polycommit domain error"))?;
-        let evals = Evaluations::from_vec_and_domain(col[0..k].to_vec(), poly_domain);
-        let poly = evals.interpolate();
+        let parity = ColumnDomain::new(k, n)?.parity(&col[0..k])?;
+        for (i, eval) in (k..n).zip(parity) {
+            data.set(i,c,eval)?;
+        }
+        Ok(())
+    }
+
+    fn update_parity(data: &mut Matrix<F>, c: usize, changed_rows: &[usize], old_vals: &[F]) -> Result<()> {
+        check_changed_rows(&data.params, c, changed_rows, old_vals.len())?;
+        let n = data.params.n;
+        let k = data.params.k;
 
-        for i in k..n{
-                let eval = poly.evaluate(&poly_domain.element(i));
-                data.set(i,c,eval)?;
+        let deltas = changed_rows.iter().zip(old_vals)
+            .map(|(&r, old)| Ok((r, data.get(r, c)? - old)))
+            .collect::<Result<Vec<_>>>()?;
+        let parity_delta = ColumnDomain::new(k, n)?.parity_delta(&deltas)?;
+        for (row, d) in data.elms[k..n].iter_mut().zip(parity_delta) {
+            row[c] += d;
         }
         Ok(())
     }
 
-    fn reconstruct(_params: Params, _matrix_opts: &mut Vec<Option<Vec<F>>>) -> Result<()> {
-        todo!()
+    /// reconstruct the missing rows (data and parity) in place from any `k` surviving rows.
+    /// each missing row is a linear combination of the surviving rows with the Lagrange
+    /// coefficients of its domain point, so all columns are recovered together.
+    fn reconstruct(params: Params, matrix_opts: &mut Vec<Option<Vec<F>>>) -> Result<()> {
+        let n = params.n;
+        let k = params.k;
+        if matrix_opts.len() != n {
+            return Err(anyhow!("expected {} rows, got {}", n, matrix_opts.len()));
+        }
+
+        // take the first k surviving rows
+        let survivors: Vec<usize> = (0..n).filter(|&i| matrix_opts[i].is_some()).take(k).collect();
+        if survivors.len() < k {
+            return Err(anyhow!("not enough rows to reconstruct: {} surviving, need k = {}", survivors.len(), k));
+        }
+        for &i in &survivors {
+            let len = matrix_opts[i].as_ref().map(|row| row.len()).unwrap_or_default();
+            if len != params.m {
+                return Err(anyhow!("row {} has length {}, expected m = {}", i, len, params.m));
+            }
+        }
+
+        // interpolate through the surviving rows and the virtual zero rows,
+        // the latter contribute nothing so only the surviving coefficients are used
+        let domain = ColumnDomain::new(k, n)?;
+        let mut points: Vec<F> = survivors.iter().map(|&i| domain.row_point(i)).collect();
+        points.extend(domain.virtual_points());
+        let basis = LagrangeBasis::new(points)?;
+
+        for i in 0..n {
+            if matrix_opts[i].is_some() {
+                continue;
+            }
+            let coeffs = basis.coeffs(domain.row_point(i))?;
+            let mut row = vec![F::zero(); params.m];
+            for (l, &s) in coeffs.iter().zip(&survivors) {
+                let surviving_row = matrix_opts[s].as_ref().unwrap();
+                for (cell, v) in row.iter_mut().zip(surviving_row) {
+                    *cell += *l * v;
+                }
+            }
+            matrix_opts[i] = Some(row);
+        }
+        Ok(())
     }
-}
\ No newline at end of file
+
+    fn is_codeword(params: Params, col: &[F]) -> Result<bool> {
+        if col.len() != params.n {
+            return Ok(false);
+        }
+        let parity = ColumnDomain::new(params.k, params.n)?.parity(&col[0..params.k])?;
+        Ok(parity == col[params.k..])
+    }
+}
+
+// --------- BLS Encoder over FatMatrix ----------------
+
+/// Encoder for fat cell matrices, each lane of a column is encoded
+/// as a separate field column with the [`BLSFieldEncoder`] code
+pub struct BLSFatEncoder<T>{
+    phantom_data: PhantomData<T>
+}
+
+impl Encoder<Vec<F>> for BLSFatEncoder<Vec<F>>{
+    type Params = Params;
+    type DataMatrix<T> = FatMatrix<F>;
+
+    /// the lanes of all columns are encoded in parallel with the `parallel` feature
+    fn encode(data: &mut FatMatrix<F>) -> Result<()> {
+        let (k, n) = (data.params.k, data.params.n);
+        let domain = ColumnDomain::new(k, n)?;
+        let matrix = &*data;
+        let parities = cfg_into_iter!(0..data.params.m * data.cell_size)
+            .map(|idx| {
+                let lane = matrix.get_lane(idx / matrix.cell_size, idx % matrix.cell_size)?;
+                domain.parity(&lane[0..k])
+            })
+            .collect::<Result<Vec<_>>>()?;
+        for (idx, parity) in parities.into_iter().enumerate() {
+            for (row, eval) in data.elms[k..n].iter_mut().zip(parity) {
+                row[idx] = eval;
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_col(data: &mut FatMatrix<F>, c: usize) -> Result<()> {
+        let n = data.params.n;
+        let k = data.params.k;
+        let domain = ColumnDomain::new(k, n)?;
+
+        for l in 0..data.cell_size {
+            let lane = data.get_lane(c, l)?;
+            let parity = domain.parity(&lane[0..k])?;
+            let idx = data.flat_index(c, l);
+            for (row, eval) in data.elms[k..n].iter_mut().zip(parity) {
+                row[idx] = eval;
+            }
+        }
+        Ok(())
+    }
+
+    /// every lane is updated with the deltas of its own cells
+    fn update_parity(data: &mut FatMatrix<F>, c: usize, changed_rows: &[usize], old_vals: &[Vec<F>]) -> Result<()> {
+        check_changed_rows(&data.params, c, changed_rows, old_vals.len())?;
+        let n = data.params.n;
+        let k = data.params.k;
+        let s = data.cell_size;
+        if old_vals.iter().any(|cell| cell.len() != s) {
+            return Err(anyhow!("old cells must have cell_size = {} elements", s));
+        }
+        let domain = ColumnDomain::new(k, n)?;
+
+        for l in 0..s {
+            let idx = data.flat_index(c, l);
+            let deltas: Vec<(usize, F)> = changed_rows.iter().zip(old_vals)
+                .map(|(&r, old)| (r, data.elms[r][idx] - old[l]))
+                .collect();
+            let parity_delta = domain.parity_delta(&deltas)?;
+            for (row, d) in data.elms[k..n].iter_mut().zip(parity_delta) {
+                row[idx] += d;
+            }
+        }
+        Ok(())
+    }
+
+    /// reconstruct the missing rows in place, all lanes are recovered at once
+    /// by reconstructing the flat rows. the cell size is read from the surviving cells
+    fn reconstruct(params: Params, matrix_opts: &mut Vec<Option<Vec<Vec<F>>>>) -> Result<()> {
+        let mut cells = matrix_opts.iter().flatten().flatten();
+        let s = cells.next().map(|cell| cell.len()).ok_or(anyhow!("no surviving cells to reconstruct from"))?;
+        if s == 0 || cells.any(|cell| cell.len() != s) {
+            return Err(anyhow!("the surviving cells must all have the same non-zero size"));
+        }
+        let flat_params = Params{
+            m: params.m * s,
+            ..params
+        };
+
+        let mut flat_opts: Vec<Option<Vec<F>>> = matrix_opts
+            .iter()
+            .map(|row| row.as_ref().map(|cells| cells.concat()))
+            .collect();
+        BLSFieldEncoder::reconstruct(flat_params, &mut flat_opts)?;
+
+        for (row, flat) in matrix_opts.iter_mut().zip(flat_opts) {
+            if row.is_none() {
+                *row = flat.map(|flat| flat.chunks(s).map(|cell| cell.to_vec()).collect());
+            }
+        }
+        Ok(())
+    }
+
+    /// the cells of the column must all have the same size
+    fn is_codeword(params: Params, col: &[Vec<F>]) -> Result<bool> {
+        let cell_size = col.first().map(|cell| cell.len()).unwrap_or_default();
+        if col.len() != params.n || cell_size == 0 || col.iter().any(|cell| cell.len() != cell_size) {
+            return Ok(false);
+        }
+        for l in 0..cell_size {
+            let lane: Vec<F> = col.iter().map(|cell| cell[l]).collect();
+            if !BLSFieldEncoder::is_codeword(params.clone(), &lane)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+// --------------- Utils -----------------
+
+/// the data rows whose cell differs between the old and the new `k` data cells of a column,
+/// with their old cells, i.e. the arguments of `update_parity`
+pub fn changed_cells<T: Clone + PartialEq>(old_col: &[T], new_col: &[T]) -> (Vec<usize>, Vec<T>) {
+    new_col.iter()
+        .zip(old_col)
+        .enumerate()
+        .filter(|(_, (new, old))| new != old)
+        .map(|(i, (_, old))| (i, old.clone()))
+        .unzip()
+}
+
+/// check the arguments of `update_parity`: one old value per changed row,
+/// the changed rows are distinct data rows of column `c`
+fn check_changed_rows(params: &Params, c: usize, changed_rows: &[usize], num_old_vals: usize) -> Result<()> {
+    params.check_cols(c)?;
+    if changed_rows.len() != num_old_vals {
+        return Err(anyhow!("{} changed rows but {} old values", changed_rows.len(), num_old_vals));
+    }
+    for (i, &r) in changed_rows.iter().enumerate() {
+        if r >= params.k {
+            return Err(anyhow!("row {} is not a data row (k = {})", r, params.k));
+        }
+        if changed_rows[..i].contains(&r) {
+            return Err(anyhow!("row {} is changed more than once", r));
+        }
+    }
+    Ok(())
+}
+
+/// serialize a field element into `FIELD_CELL_BYTES` little-endian bytes
+fn field_to_bytes(v: F) -> Vec<u8> {
+    v.into_bigint().to_bytes_le()
+}
+
+/// deserialize a field element from exactly `FIELD_CELL_BYTES` little-endian bytes,
+/// fails instead of reducing a value that is not below the field modulus
+fn field_from_bytes(bytes: &[u8]) -> Result<F> {
+    if bytes.len() != FIELD_CELL_BYTES {
+        return Err(anyhow!("field cell has {} bytes, expected {}", bytes.len(), FIELD_CELL_BYTES));
+    }
+    let int = <F as PrimeField>::BigInt::deserialize_uncompressed(bytes)?;
+    F::from_bigint(int).ok_or(anyhow!("field cell is not below the field modulus"))
+}
+
+/// convert a field element back to a byte, if it is one
+fn field_to_byte(v: F) -> Option<u8> {
+    let bytes = field_to_bytes(v);
+    if bytes[1..].iter().all(|b| *b == 0) {
+        Some(bytes[0])
+    } else {
+        None
+    }
+}
+
+/// the Lagrange basis through a set of distinct points in barycentric form,
+/// the weights `w_j = 1 / prod_{i != j} (x_j - x_i)` are computed once in `O(k^2)`
+/// so the coefficients at any other point cost `O(k)`
+struct LagrangeBasis {
+    points: Vec<F>,
+    weights: Vec<F>,
+}
+
+impl LagrangeBasis {
+    fn new(points: Vec<F>) -> Result<Self> {
+        let mut weights: Vec<F> = points.iter().enumerate()
+            .map(|(j, x_j)| points.iter().enumerate().filter(|(i, _)| *i != j).map(|(_, x_i)| *x_j - x_i).product())
+            .collect();
+        if weights.iter().any(|w| w.is_zero()) {
+            return Err(anyhow!("interpolation points must be distinct"));
+        }
+        batch_inversion(&mut weights);
+        Ok(Self{
+            points,
+            weights,
+        })
+    }
+
+    /// Lagrange coefficients of the interpolating polynomial evaluated at `x`,
+    /// i.e. `l_j(x) = prod_i (x - x_i) * w_j / (x - x_j)` for every point `x_j`
+    fn coeffs(&self, x: F) -> Result<Vec<F>> {
+        let mut diffs: Vec<F> = self.points.iter().map(|x_j| x - x_j).collect();
+        if diffs.iter().any(|d| d.is_zero()) {
+            return Err(anyhow!("evaluation point is one of the interpolation points"));
+        }
+        let vanishing: F = diffs.iter().product();
+        batch_inversion(&mut diffs);
+        Ok(diffs.iter().zip(&self.weights).map(|(d, w)| vanishing * w * d).collect())
+    }
+}
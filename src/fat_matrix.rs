@@ -0,0 +1,225 @@
+use ark_ff::Field;
+use ark_std::test_rng;
+use crate::byte_data::{check_truncate, Params};
+use crate::traits::DataMatrix;
+
+/// a Field matrix with `n` rows and `m` columns where each cell holds `cell_size` field elements.
+/// the cell elements are called lanes, lane `l` of all cells in a column forms a regular field column.
+/// rows are stored flat, i.e. cell (r,c) is `elms[r][c*cell_size..(c+1)*cell_size]`
+#[derive(Clone, Debug)]
+pub struct FatMatrix<F: Field + Clone> {
+    pub params: Params,
+    /// number of field elements (lanes) in each cell
+    pub cell_size: usize,
+    pub elms: Vec<Vec<F>>,
+}
+
+/// parameters of a fat cell matrix: the `n`x`m` matrix params and the number of elements in each cell
+#[derive(Clone, Debug)]
+pub struct FatParams {
+    pub params: Params,
+    pub cell_size: usize,
+}
+
+impl<F: Field + Clone> FatMatrix<F> {
+
+    /// position of lane `l` of column `c` in the flat row
+    pub fn flat_index(&self, c: usize, l: usize) -> usize {
+        c * self.cell_size + l
+    }
+
+    /// the params of the flat view of the matrix, i.e. with `m * cell_size` single-element cells
+    pub fn flat_params(&self) -> Params {
+        Params{
+            k: self.params.k,
+            n: self.params.n,
+            m: self.params.m * self.cell_size,
+        }
+    }
+
+    /// the flat row at 0<idx<n, with all lanes of all cells
+    pub fn get_flat_row(&self, idx: usize) -> anyhow::Result<&[F]> {
+        self.params.check_rows(idx)?;
+        Ok(&self.elms[idx])
+    }
+
+    /// lane `l` of column `c`
+    pub fn get_lane(&self, c: usize, l: usize) -> anyhow::Result<Vec<F>> {
+        self.params.check_cols(c)?;
+        self.check_lane(l)?;
+        let idx = self.flat_index(c, l);
+        Ok(self.elms
+            .iter()
+            .map(|row| row[idx])
+            .collect())
+    }
+
+    /// set lane `l` of column `c`, `lane` may cover only the first rows (e.g. the `k` data rows)
+    pub fn set_lane(&mut self, c: usize, l: usize, lane: &[F]) -> anyhow::Result<()> {
+        self.params.check_cols(c)?;
+        self.check_lane(l)?;
+        assert!(
+            lane.len() <= self.params.n,
+            "lane length ({}) must be at most n ({})",
+            lane.len(),
+            self.params.n
+        );
+        let idx = self.flat_index(c, l);
+        for (r, val) in lane.iter().enumerate() {
+            self.elms[r][idx] = *val;
+        }
+        Ok(())
+    }
+
+    fn check_lane(&self, l: usize) -> anyhow::Result<()> {
+        assert!(
+            l < self.cell_size,
+            "lane index {} out of bounds; must be < {}",
+            l,
+            self.cell_size
+        );
+        Ok(())
+    }
+
+    fn check_cell(&self, cell: &[F]) -> anyhow::Result<()> {
+        assert!(
+            cell.len() == self.cell_size,
+            "cell length ({}) must equal cell_size ({})",
+            cell.len(),
+            self.cell_size
+        );
+        Ok(())
+    }
+}
+
+impl<F: Field + Clone> DataMatrix<Vec<F>> for FatMatrix<F> {
+    type Params = FatParams;
+
+    /// Generates a random matrix with given dimensions and cell size
+    fn new_random(fat_params: FatParams) -> Self
+    {
+        let FatParams { params, cell_size } = fat_params;
+        let mut rng = test_rng();
+        let rows = params.n;
+        let cols = params.m * cell_size;
+        let mut data = Vec::with_capacity(rows);
+        for _ in 0..rows {
+            let mut row = Vec::with_capacity(cols);
+            for _ in 0..cols {
+                row.push(F::rand(&mut rng));
+            }
+            data.push(row);
+        }
+        FatMatrix { params, cell_size, elms: data }
+    }
+
+    fn get(&self, r: usize, c: usize) -> anyhow::Result<Vec<F>> {
+        self.params.check_bounds(r,c)?;
+        let s = self.cell_size;
+        Ok(self.elms[r][c * s..(c + 1) * s].to_vec())
+    }
+
+    fn set(&mut self, r: usize, c: usize, elem: Vec<F>) -> anyhow::Result<()>{
+        self.params.check_bounds(r,c)?;
+        self.check_cell(&elem)?;
+        let s = self.cell_size;
+        self.elms[r][c * s..(c + 1) * s].copy_from_slice(&elem);
+        Ok(())
+    }
+
+    /// get the row at 0<idx<n as a list of cells
+    fn get_row(&self, idx: usize) -> anyhow::Result<Vec<Vec<F>>>{
+        self.params.check_rows(idx)?;
+        Ok(self.elms[idx]
+            .chunks(self.cell_size)
+            .map(|cell| cell.to_vec())
+            .collect())
+    }
+
+    fn get_col(&self, idx: usize) -> anyhow::Result<Vec<Vec<F>>> {
+        self.params.check_cols(idx)?;
+        let s = self.cell_size;
+        Ok(self.elms
+            .iter()
+            .map(|row| row[idx * s..(idx + 1) * s].to_vec())
+            .collect())
+    }
+
+    /// Print matrix, one cell per bracket
+    fn pretty_print(&self) {
+        for (i, row) in self.elms.iter().enumerate() {
+            print!("row {:>2}: ", i);
+            for cell in row.chunks(self.cell_size) {
+                print!("[");
+                for b in cell {
+                    print!("{:>3} ", b);
+                }
+                print!("] ");
+            }
+            println!();
+        }
+    }
+
+    fn update_col(&mut self, c: usize, new_col: &[Vec<F>]) -> anyhow::Result<()> {
+        self.params.check_cols(c)?;
+
+        // ensure the provided column has exactly `k` cells
+        assert!(
+            new_col.len() == self.params.k,
+            "new_col length ({}) must equal k ({})",
+            new_col.len(),
+            self.params.k
+        );
+
+        for (r, cell) in new_col.iter().enumerate() {
+            self.set(r, c, cell.clone())?;
+        }
+
+        Ok(())
+    }
+
+    fn append_cols(&mut self, new_cols: &[Vec<Vec<F>>]) -> anyhow::Result<()> {
+        for col in new_cols {
+            assert!(
+                col.len() == self.params.k,
+                "new_col length ({}) must equal k ({})",
+                col.len(),
+                self.params.k
+            );
+            for cell in col {
+                self.check_cell(cell)?;
+            }
+        }
+        let s = self.cell_size;
+        for (r, row) in self.elms.iter_mut().enumerate() {
+            if r < self.params.k {
+                row.extend(new_cols.iter().flat_map(|col| col[r].iter().copied()));
+            } else {
+                row.resize(row.len() + new_cols.len() * s, F::ZERO);
+            }
+        }
+        self.params.m += new_cols.len();
+        Ok(())
+    }
+
+    fn truncate_cols(&mut self, m: usize) -> anyhow::Result<()> {
+        check_truncate(&self.params, m)?;
+        let s = self.cell_size;
+        for row in self.elms.iter_mut() {
+            row.truncate(m * s);
+        }
+        self.params.m = m;
+        Ok(())
+    }
+
+    fn delete_col(&mut self, c: usize) -> anyhow::Result<()> {
+        self.params.check_cols(c)?;
+        check_truncate(&self.params, self.params.m - 1)?;
+        let s = self.cell_size;
+        for row in self.elms.iter_mut() {
+            row.drain(c * s..(c + 1) * s);
+        }
+        self.params.m -= 1;
+        Ok(())
+    }
+}
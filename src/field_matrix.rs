@@ -1,6 +1,6 @@
-use ark_ff::Field;
+use ark_ff::{BigInteger, Field, PrimeField};
 use ark_std::{test_rng};
-use crate::byte_data::{Data, Params};
+use crate::byte_data::{check_truncate, Data, Params};
 use crate::traits::DataMatrix;
 
 
@@ -43,6 +43,73 @@ impl<F: Field + Clone> Matrix<F> {
     }
 }
 
+/// number of bytes that fit losslessly in one element of the prime field `F`,
+/// i.e. the largest `b` with `2^(8b) <= p` (31 for both BLS12-381 and BN254)
+pub fn packed_bytes_per_elm<F: PrimeField>() -> usize {
+    ((F::MODULUS_BIT_SIZE - 1) / 8) as usize
+}
+
+/// byte packing, each field element holds `packed_bytes_per_elm` bytes of a row
+impl<F: PrimeField> Matrix<F> {
+
+    /// Creates a new matrix from the `k` data rows of the given u8 data struct,
+    /// packing consecutive bytes of each row into field elements (little endian).
+    /// the last element of a row is zero padded when `m` is not a multiple of the packing size.
+    /// the resulting matrix has `ceil(m / packed_bytes_per_elm)` columns and its parity rows are zero.
+    pub fn from_data_packed(data: &Data<u8>) -> Self{
+        let bytes_per_elm = packed_bytes_per_elm::<F>();
+        let params = Params{
+            m: data.params.m.div_ceil(bytes_per_elm),
+            ..data.params.clone()
+        };
+
+        let mut field_data = Vec::with_capacity(params.n);
+        for i in 0..params.k {
+            let row = data.matrix[i][0..data.params.m]
+                .chunks(bytes_per_elm)
+                .map(F::from_le_bytes_mod_order)
+                .collect();
+            field_data.push(row);
+        }
+        field_data.resize(params.n, vec![F::ZERO; params.m]);
+        Matrix { params, elms: field_data }
+    }
+
+    /// Unpacks the `k` data rows back into a u8 data struct with `byte_cols` columns,
+    /// dropping the tail padding. The parity rows of the result are zero.
+    pub fn to_data_packed(&self, byte_cols: usize) -> anyhow::Result<Data<u8>>{
+        let bytes_per_elm = packed_bytes_per_elm::<F>();
+        if byte_cols.div_ceil(bytes_per_elm) != self.params.m {
+            return Err(anyhow::anyhow!(
+                "{} byte columns don't pack into {} field columns",
+                byte_cols,
+                self.params.m
+            ));
+        }
+        let params = Params{
+            m: byte_cols,
+            ..self.params.clone()
+        };
+
+        let mut matrix = Vec::with_capacity(params.n);
+        for i in 0..params.k {
+            let mut row = Vec::with_capacity(self.params.m * bytes_per_elm);
+            for elm in &self.elms[i] {
+                let mut bytes = elm.into_bigint().to_bytes_le();
+                if bytes[bytes_per_elm..].iter().any(|b| *b != 0) {
+                    return Err(anyhow::anyhow!("element in row {} is not a packed element", i));
+                }
+                bytes.truncate(bytes_per_elm);
+                row.extend(bytes);
+            }
+            row.truncate(byte_cols);
+            matrix.push(row);
+        }
+        matrix.resize(params.n, vec![0u8; byte_cols]);
+        Ok(Data { params, matrix })
+    }
+}
+
 impl<F: Field + Clone> DataMatrix<F> for Matrix<F> {
     type Params = Params;
 
@@ -65,12 +132,13 @@ impl<F: Field + Clone> DataMatrix<F> for Matrix<F> {
 
     fn get(&self, r: usize, c: usize) -> anyhow::Result<F> {
         self.params.check_bounds(r,c)?;
-        Ok(self.elms[r][c].clone())
+        Ok(self.elms[r][c])
     }
 
     fn set(&mut self, r: usize, c: usize, elem: F) -> anyhow::Result<()>{
         self.params.check_bounds(r,c)?;
-        Ok(self.elms[r][c] = elem)
+        self.elms[r][c] = elem;
+        Ok(())
     }
 
     /// get the row at 0<idx<n
@@ -83,7 +151,7 @@ impl<F: Field + Clone> DataMatrix<F> for Matrix<F> {
         self.params.check_cols(idx)?;
         Ok(self.elms
             .iter()
-            .map(|row| row[idx].clone())
+            .map(|row| row[idx])
             .collect())
     }
 
@@ -110,9 +178,48 @@ impl<F: Field + Clone> DataMatrix<F> for Matrix<F> {
         );
 
         for (r, val) in new_col.iter().enumerate() {
-            self.elms[r][c] = val.clone();
+            self.elms[r][c] = *val;
+        }
+
+        Ok(())
+    }
+
+    fn append_cols(&mut self, new_cols: &[Vec<F>]) -> anyhow::Result<()> {
+        for col in new_cols {
+            assert!(
+                col.len() == self.params.k,
+                "new_col length ({}) must equal k ({})",
+                col.len(),
+                self.params.k
+            );
+        }
+        for (r, row) in self.elms.iter_mut().enumerate() {
+            if r < self.params.k {
+                row.extend(new_cols.iter().map(|col| col[r]));
+            } else {
+                row.resize(row.len() + new_cols.len(), F::ZERO);
+            }
+        }
+        self.params.m += new_cols.len();
+        Ok(())
+    }
+
+    fn truncate_cols(&mut self, m: usize) -> anyhow::Result<()> {
+        check_truncate(&self.params, m)?;
+        for row in self.elms.iter_mut() {
+            row.truncate(m);
         }
+        self.params.m = m;
+        Ok(())
+    }
 
+    fn delete_col(&mut self, c: usize) -> anyhow::Result<()> {
+        self.params.check_cols(c)?;
+        check_truncate(&self.params, self.params.m - 1)?;
+        for row in self.elms.iter_mut() {
+            row.remove(c);
+        }
+        self.params.m -= 1;
         Ok(())
     }
 }
@@ -1,14 +1,23 @@
 use ark_poly::univariate::DensePolynomial;
-use ark_poly::DenseUVPolynomial;
+use ark_poly::univariate::DenseOrSparsePolynomial;
+use ark_poly::{DenseUVPolynomial, Polynomial};
 use ark_poly_commit::{
     LabeledPolynomial,
 };
-use ark_std::test_rng;
-use anyhow::Result;
+use ark_std::rand::{CryptoRng, RngCore};
+use ark_std::{cfg_into_iter, test_rng};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+use std::marker::PhantomData;
 use ark_bls12_381::Bls12_381;
 use ark_ec::pairing::Pairing;
-use ark_ec::{AffineRepr, CurveGroup};
-use ark_ff::{PrimeField, Zero};
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, PrimeField, Zero};
+use ark_poly_commit::PCCommitmentState;
+use crate::srs::{load_srs, SetupSource};
+use crate::transcript::Transcript;
 use crate::traits::{CommitOutputTrait, PolyCommScheme};
 use ark_poly_commit::kzg10::{KZG10, Proof, UniversalParams, Powers, VerifierKey, Commitment, Randomness};
 
@@ -17,21 +26,40 @@ pub type F = <E as Pairing>::ScalarField;
 pub type UniPoly381 = DensePolynomial<F>;
 pub type PCS = KZG10<E, UniPoly381>;
 
-pub type KZG10SRS = UniversalParams<E>;
+pub type KZG10SRS<Curve = Bls12_381> = UniversalParams<Curve>;
 
-pub struct KZG10PolyComm {}
+/// scalar field, row polynomials and KZG scheme of the pairing engine `Curve`
+pub type Fr<Curve> = <Curve as Pairing>::ScalarField;
+pub type UniPoly<Curve> = DensePolynomial<Fr<Curve>>;
+type Kzg<Curve> = KZG10<Curve, UniPoly<Curve>>;
 
-pub struct KZG10CommitOutput {
-    pub poly: LabeledPolynomial<F, UniPoly381>,
+/// KZG10 commitments to polynomials in coefficient form over the pairing engine `E`,
+/// BLS12-381 by default
+pub struct KZG10PolyComm<E: Pairing = Bls12_381> {
+    phantom_data: PhantomData<E>,
+}
+
+#[derive(Clone)]
+pub struct KZG10CommitOutput<E: Pairing = Bls12_381> {
+    pub poly: LabeledPolynomial<E::ScalarField, UniPoly<E>>,
     pub comm: Commitment<E>,
-    pub rand: Randomness<F, UniPoly381>,
+    pub rand: Randomness<E::ScalarField, UniPoly<E>>,
+}
+
+/// multi-point opening proof: `quotient` commits to `q = (p - I) / Z_S`, where `I` interpolates the values
+/// over the points `S` and `Z_S` vanishes on them, and `proof` opens `p - I(z) - Z_S(z) * q` to zero at the
+/// Fiat–Shamir point `z`, so only `h` and `beta_h` are needed in G2
+#[derive(Clone, Debug, PartialEq)]
+pub struct KZG10MultiProof<E: Pairing = Bls12_381> {
+    pub quotient: Commitment<E>,
+    pub proof: Proof<E>,
 }
 
-impl KZG10CommitOutput {
+impl<E: Pairing> KZG10CommitOutput<E> {
     pub fn new(
-        poly: LabeledPolynomial<F, UniPoly381>,
+        poly: LabeledPolynomial<E::ScalarField, UniPoly<E>>,
         comm: Commitment<E>,
-        rand: Randomness<F, UniPoly381>,
+        rand: Randomness<E::ScalarField, UniPoly<E>>,
     ) -> Self{
         Self{
             poly,
@@ -42,12 +70,12 @@ impl KZG10CommitOutput {
 }
 
 
-impl CommitOutputTrait for KZG10CommitOutput {
-    type Poly = LabeledPolynomial<F, UniPoly381>;
+impl<E: Pairing> CommitOutputTrait for KZG10CommitOutput<E> {
+    type Poly = LabeledPolynomial<E::ScalarField, UniPoly<E>>;
     type Comm = Commitment<E>;
-    type Rand = Randomness<F, UniPoly381>;
+    type Rand = Randomness<E::ScalarField, UniPoly<E>>;
 
-    fn get_poly(&self) -> &LabeledPolynomial<F, UniPoly381>{
+    fn get_poly(&self) -> &LabeledPolynomial<E::ScalarField, UniPoly<E>>{
         &self.poly
     }
 
@@ -55,13 +83,13 @@ impl CommitOutputTrait for KZG10CommitOutput {
         &self.comm
     }
 
-    fn get_rand(&self) -> &Randomness<F, UniPoly381>{
+    fn get_rand(&self) -> &Randomness<E::ScalarField, UniPoly<E>>{
         &self.rand
     }
 }
 
-impl KZG10PolyComm{
-    fn commit_single(srs: &KZG10SRS, input: F, index: usize) -> Result<Commitment<E>> {
+impl<E: Pairing> KZG10PolyComm<E> {
+    fn commit_single(srs: &KZG10SRS<E>, input: E::ScalarField, index: usize) -> Result<Commitment<E>> {
         let power = &srs.powers_of_g[index];
 
         let c = power.mul_bigint(input.into_bigint());
@@ -70,81 +98,150 @@ impl KZG10PolyComm{
             Commitment::<E>(c.into_affine())
         )
     }
-}
 
-impl PolyCommScheme<F> for KZG10PolyComm {
-    type SRS = KZG10SRS;
-    type VK = VerifierKey<E>;
-    type CommitOutput = KZG10CommitOutput;
-    type Comm = Commitment<E>;
-    type Proof = Proof<E>;
-
-    fn setup(degree: usize) -> Result<Self::SRS> {
-        let rng = &mut test_rng();
-        let pp = PCS::setup(degree,false, rng)?;
-        Ok(pp)
-    }
 
-    fn commit(srs: &Self::SRS, input: Vec<F>) -> Result<Self::CommitOutput> {
-        let rng = &mut test_rng();
+    /// commit to the poly with the given coeffs, hiding if `hiding_bound` is given
+    fn commit_with(
+        srs: &KZG10SRS<E>,
+        input: Vec<E::ScalarField>,
+        hiding_bound: Option<usize>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<KZG10CommitOutput<E>> {
         let degree = input.len();
-        let powers = get_powers(&srs, degree)?;
+        let powers = get_powers(srs, degree)?;
 
         // input are poly coeffs
-        let input_poly = DensePolynomial::<F>::from_coefficients_vec(input);
+        let input_poly = UniPoly::<E>::from_coefficients_vec(input);
         let label = String::from("row_poly");
         let labeled_poly = LabeledPolynomial::new(
             label,
             input_poly,
             Some(degree),
-            None,
+            hiding_bound,
         );
 
-        let (comm, rand) = PCS::commit(&powers, &labeled_poly, None, Some(rng))?;
+        let (comm, rand) = Kzg::<E>::commit(&powers, &labeled_poly, hiding_bound, rng)?;
 
         Ok(
             KZG10CommitOutput::new(labeled_poly, comm, rand)
         )
     }
+}
+
+impl<E: Pairing> PolyCommScheme<E::ScalarField> for KZG10PolyComm<E> {
+    type SRS = KZG10SRS<E>;
+    type VK = VerifierKey<E>;
+    type CommitOutput = KZG10CommitOutput<E>;
+    type Comm = Commitment<E>;
+    type Proof = Proof<E>;
+    type MultiProof = KZG10MultiProof<E>;
+
+    fn setup(degree: usize, source: &SetupSource) -> Result<Self::SRS> {
+        match source {
+            SetupSource::File { path, format } => load_srs::<E>(path, *format, degree),
+            SetupSource::InsecureTest => {
+                let rng = &mut test_rng();
+                let pp = Kzg::<E>::setup(degree,false, rng)?;
+                Ok(pp)
+            }
+        }
+    }
+
+    fn vk(srs: &Self::SRS) -> Result<Self::VK> {
+        get_vk(srs)
+    }
+
+    /// a row of `m` cells is committed with the powers up to degree `m`
+    fn max_cells(srs: &Self::SRS) -> usize {
+        srs.powers_of_g.len() - 1
+    }
+
+    fn commit(srs: &Self::SRS, input: Vec<E::ScalarField>) -> Result<Self::CommitOutput> {
+        Self::commit_with(srs, input, None, None)
+    }
+
+    fn commit_hiding<R: RngCore + CryptoRng>(srs: &Self::SRS, input: Vec<E::ScalarField>, hiding_bound: usize, rng: &mut R) -> Result<Self::CommitOutput> {
+        check_gamma_powers(srs, hiding_bound)?;
+        Self::commit_with(srs, input, Some(hiding_bound), Some(rng))
+    }
+
+    /// adds a fresh blinding polynomial, i.e. `comm += sum_j r_j * gamma_g_j` and `rand += r`
+    fn rerandomize<R: RngCore + CryptoRng>(srs: &Self::SRS, comm: &mut Self::CommitOutput, rng: &mut R) -> Result<()> {
+        let hiding_bound = comm.poly.hiding_bound()
+            .ok_or(anyhow!("cannot rerandomize a non-hiding commitment"))?;
+        check_gamma_powers(srs, hiding_bound)?;
+
+        let fresh = Randomness::<E::ScalarField, UniPoly<E>>::rand(hiding_bound, false, None, rng);
+        let blinding = fresh.blinding_polynomial.coeffs();
+        let bases: Vec<_> = (0..blinding.len()).map(|i| srs.powers_of_gamma_g[&i]).collect();
+        let blinding_comm = <E as Pairing>::G1::msm(&bases, blinding)
+            .map_err(|_| anyhow!("msm bases and scalars length mismatch"))?;
+        comm.comm.0 = (comm.comm.0 + blinding_comm).into_affine();
+        comm.rand += &fresh;
+        Ok(())
+    }
 
-    fn update_commitment(srs: &Self::SRS, original_comm: &mut Self::CommitOutput, original_cell: F, new_cell:F, index: usize) -> Result<()> {
+    fn update_commitment(srs: &Self::SRS, original_comm: &mut Self::CommitOutput, original_cell: E::ScalarField, new_cell:E::ScalarField, index: usize) -> Result<()> {
         // check if there is difference,
-        if new_cell.clone() - original_cell.clone() == F::zero() {
+        let delta = new_cell - original_cell;
+        if delta == E::ScalarField::zero() {
             return Ok(())
         }
+        check_index(srs, index)?;
 
-        // commit to original and new cells
-        let original_cell_comm = Self::commit_single(srs, original_cell, index)?;
-        let new_cell_comm = Self::commit_single(srs, new_cell.clone(), index)?;
+        // commit to the delta and update the commitment
+        let delta_comm = Self::commit_single(srs, delta, index)?;
+        original_comm.comm.0 = (original_comm.comm.0 + delta_comm.0).into_affine();
+        // update the poly
+        set_cells(original_comm, &[(index, new_cell)]);
+        // no update to rand, the blinding polynomial doesn't depend on the cells
+        Ok(())
+    }
+
+    /// the commitment is updated with a single MSM of the cell deltas against `powers_of_g`
+    fn update_commitment_batch(srs: &Self::SRS, original_comm: &mut Self::CommitOutput, cells: &[(usize, E::ScalarField, E::ScalarField)]) -> Result<()> {
+        check_distinct_cells(cells)?;
+        let mut bases = Vec::with_capacity(cells.len());
+        let mut deltas = Vec::with_capacity(cells.len());
+        for (index, original_cell, new_cell) in cells {
+            let delta = *new_cell - original_cell;
+            if delta == E::ScalarField::zero() {
+                continue;
+            }
+            check_index(srs, *index)?;
+            bases.push(srs.powers_of_g[*index]);
+            deltas.push(delta);
+        }
+        if deltas.is_empty() {
+            return Ok(())
+        }
 
-        // compute delta
-        let delta_comm = (new_cell_comm.0-original_cell_comm.0).into_affine();
         // update the commitment
-        let mut tmp = original_comm.comm.0.clone().into_group();
-        tmp += &delta_comm;
-        original_comm.comm.0 = tmp.into_affine();
+        let delta_comm = <E as Pairing>::G1::msm(&bases, &deltas)
+            .map_err(|_| anyhow!("msm bases and scalars length mismatch"))?;
+        original_comm.comm.0 = (original_comm.comm.0 + delta_comm).into_affine();
         // update the poly
-        let original_poly = original_comm.poly.polynomial_mut();
-        original_poly.coeffs[index] = new_cell;
-        // no update to rand because we assume it is empty i.e. no hiding
+        let new_cells: Vec<_> = cells.iter().map(|(index, _, new_cell)| (*index, *new_cell)).collect();
+        set_cells(original_comm, &new_cells);
+        // no update to rand, the blinding polynomial doesn't depend on the cells
         Ok(())
     }
 
     fn open(
-            comm: &KZG10CommitOutput,
-            srs: &KZG10SRS,
-            point: F,
+            comm: &KZG10CommitOutput<E>,
+            srs: &KZG10SRS<E>,
+            point: E::ScalarField,
     ) -> Result<Self::Proof> {
 
         // powers from the srs
         let m = srs.powers_of_g.len() - 1;
-        let powers= get_powers(&srs, m)?;
+        let powers= get_powers(srs, m)?;
 
         // get row poly and rand
         let poly     = &comm.poly;
         let rand    = &comm.rand;
 
-        let proof = PCS::open(
+        let proof = Kzg::<E>::open(
             &powers,
             poly,
             point,
@@ -157,31 +254,257 @@ impl PolyCommScheme<F> for KZG10PolyComm {
     fn verify(
         vk:   &Self::VK,
         comm: &Self::Comm,
-        point: F,
-        value: F,
+        point: E::ScalarField,
+        value: E::ScalarField,
         proof: &Self::Proof,
     ) -> Result<bool> {
 
-        Ok( PCS::check(
-            &vk,
+        Ok( Kzg::<E>::check(
+            vk,
             comm,
             point,
             value,
             proof,
         )? )
     }
+
+    /// the row is committed in coefficient form so cell `index` multiplies `X^index`
+    fn cell_basis(_vk: &Self::VK, index: usize, point: E::ScalarField) -> Result<E::ScalarField> {
+        Ok(point.pow([index as u64]))
+    }
+
+    /// the cells are coefficients, so there is no point at which the row evaluates to a cell
+    fn cell_point(_vk: &Self::VK, index: usize) -> Result<E::ScalarField> {
+        Err(anyhow!("cell {} of a row in coefficient form is not an evaluation, commit in Lagrange form to open cells", index))
+    }
+
+    fn open_multi(
+        comm: &KZG10CommitOutput<E>,
+        srs: &KZG10SRS<E>,
+        points: &[E::ScalarField],
+    ) -> Result<(Vec<E::ScalarField>, Self::MultiProof)> {
+        let vanishing = vanishing_poly::<E::ScalarField>(points)?;
+        let poly = comm.poly.polynomial();
+        let values: Vec<E::ScalarField> = points.iter().map(|z| poly.evaluate(z)).collect();
+
+        // p = q * Z_S + I, the remainder interpolates the values
+        let (quotient, remainder) = DenseOrSparsePolynomial::from(poly)
+            .divide_with_q_and_r(&DenseOrSparsePolynomial::from(&vanishing))
+            .ok_or(anyhow!("division by the vanishing polynomial failed"))?;
+        let m = srs.powers_of_g.len() - 1;
+        let powers = get_powers(srs, m)?;
+        let (quotient_comm, _) = Kzg::<E>::commit(&powers, &quotient, None, None)?;
+
+        let z = multi_open_challenge::<E>(&comm.comm, &quotient_comm, points, &values)?;
+        // l vanishes at z, its blinding is the one of p
+        let mut l = poly - &(&quotient * vanishing.evaluate(&z));
+        l -= &UniPoly::<E>::from_coefficients_vec(vec![remainder.evaluate(&z)]);
+        let proof = Kzg::<E>::open(&powers, &l, z, &comm.rand)?;
+
+        Ok((values, KZG10MultiProof{ quotient: quotient_comm, proof }))
+    }
+
+    fn verify_multi(
+        vk:   &Self::VK,
+        comm: &Self::Comm,
+        points: &[E::ScalarField],
+        values: &[E::ScalarField],
+        proof: &Self::MultiProof,
+    ) -> Result<bool> {
+        if points.len() != values.len() {
+            return Ok(false);
+        }
+        let vanishing = vanishing_poly::<E::ScalarField>(points)?;
+        let z = multi_open_challenge::<E>(comm, &proof.quotient, points, values)?;
+
+        // commitment to l = p - I(z) - Z_S(z) * q
+        let l = comm.0.into_group()
+            - vk.g * interpolate_at(points, values, z)
+            - proof.quotient.0 * vanishing.evaluate(&z);
+        Self::verify(vk, &Commitment::<E>(l.into_affine()), z, E::ScalarField::zero(), &proof.proof)
+    }
+
+    fn open_combination(
+        comms: &[&KZG10CommitOutput<E>],
+        coeffs: &[E::ScalarField],
+        srs: &KZG10SRS<E>,
+        point: E::ScalarField,
+    ) -> Result<Self::Proof> {
+        if comms.len() != coeffs.len() {
+            return Err(anyhow!("got {} commitments but {} coefficients", comms.len(), coeffs.len()));
+        }
+
+        // powers from the srs
+        let m = srs.powers_of_g.len() - 1;
+        let powers= get_powers(srs, m)?;
+
+        // combine the polys coefficient by coefficient, in parallel with the `parallel` feature
+        let len = comms.iter().map(|c| c.poly.coeffs.len()).max().unwrap_or_default();
+        let combined: Vec<E::ScalarField> = cfg_into_iter!(0..len)
+            .map(|i| {
+                comms.iter().zip(coeffs)
+                    .filter_map(|(comm, coeff)| comm.poly.coeffs.get(i).map(|c| *coeff * c))
+                    .sum()
+            })
+            .collect();
+        let poly = UniPoly::<E>::from_coefficients_vec(combined);
+        // and their randomness
+        let mut rand = Randomness::<E::ScalarField, UniPoly<E>>::empty();
+        for (comm, coeff) in comms.iter().zip(coeffs) {
+            rand += (*coeff, &comm.rand);
+        }
+
+        let proof = Kzg::<E>::open(
+            &powers,
+            &poly,
+            point,
+            &rand,
+        )?;
+
+        Ok(proof)
+    }
+
+    fn verify_combination(
+        vk:   &Self::VK,
+        comms: &[&Self::Comm],
+        coeffs: &[E::ScalarField],
+        point: E::ScalarField,
+        value: E::ScalarField,
+        proof: &Self::Proof,
+    ) -> Result<bool> {
+        let bases: Vec<_> = comms.iter().map(|c| c.0).collect();
+        let comm = <E as Pairing>::G1::msm(&bases, coeffs)
+            .map_err(|_| anyhow!("got {} commitments but {} coefficients", comms.len(), coeffs.len()))?;
+
+        Self::verify(
+            vk,
+            &Commitment::<E>(comm.into_affine()),
+            point,
+            value,
+            proof,
+        )
+    }
 }
 
 // --------------- Utils -----------------
 
+/// `Z_S(X) = prod_i (X - points[i])`, the points must be distinct
+fn vanishing_poly<F: PrimeField>(points: &[F]) -> Result<DensePolynomial<F>> {
+    if points.is_empty() {
+        return Err(anyhow!("no points to open"));
+    }
+    for (i, z) in points.iter().enumerate() {
+        if points[..i].contains(z) {
+            return Err(anyhow!("the opening points must be distinct"));
+        }
+    }
+    Ok(points.iter().fold(DensePolynomial::from_coefficients_vec(vec![F::one()]), |acc, z| {
+        &acc * &DensePolynomial::from_coefficients_vec(vec![-*z, F::one()])
+    }))
+}
+
+/// evaluate at `z` the polynomial interpolating `values` over `points`, with the Lagrange formula
+fn interpolate_at<F: PrimeField>(points: &[F], values: &[F], z: F) -> F {
+    let mut sum = F::zero();
+    for (i, (x_i, v_i)) in points.iter().zip(values).enumerate() {
+        let mut num = F::one();
+        let mut den = F::one();
+        for (j, x_j) in points.iter().enumerate() {
+            if i != j {
+                num *= z - x_j;
+                den *= *x_i - x_j;
+            }
+        }
+        sum += *v_i * num * den.inverse().expect("the points are distinct");
+    }
+    sum
+}
+
+/// the point `z` of a multi-point opening, bound to the commitment, the quotient commitment, the points and the values
+fn multi_open_challenge<E: Pairing>(
+    comm: &Commitment<E>,
+    quotient: &Commitment<E>,
+    points: &[E::ScalarField],
+    values: &[E::ScalarField],
+) -> Result<E::ScalarField> {
+    let mut transcript = Transcript::new(b"dde-kzg-multi-open");
+    transcript.append_serializable(b"comm", comm)?;
+    transcript.append_serializable(b"quotient", quotient)?;
+    transcript.append_serializable(b"points", &points.to_vec())?;
+    transcript.append_serializable(b"values", &values.to_vec())?;
+    Ok(transcript.challenge_field(b"z"))
+}
+
+/// the cell must have a power of g, cells past the end of the row (e.g. of appended columns) are allowed
+fn check_index<E: Pairing>(srs: &KZG10SRS<E>, index: usize) -> Result<()> {
+    let max_cells = srs.powers_of_g.len() - 1;
+    if index >= max_cells {
+        return Err(anyhow!("cell index {} exceeds the srs size of {} cells", index, max_cells));
+    }
+    Ok(())
+}
+
+/// a batch update must change each cell at most once, a repeated index would add both deltas
+/// to the commitment while the polynomial keeps only the last new value
+pub(crate) fn check_distinct_cells<F>(cells: &[(usize, F, F)]) -> Result<()> {
+    let mut indices = HashSet::with_capacity(cells.len());
+    for (index, _, _) in cells {
+        if !indices.insert(*index) {
+            return Err(anyhow!("cell {} is updated more than once in the batch", index));
+        }
+    }
+    Ok(())
+}
+
+/// set the updated coefficients, growing the poly (and its degree bound) for cells past its end,
+/// the trailing zeros are trimmed so the poly stays the one a fresh commit would give
+fn set_cells<E: Pairing>(comm: &mut KZG10CommitOutput<E>, cells: &[(usize, E::ScalarField)]) {
+    let Some(end) = cells.iter().map(|(index, _)| index + 1).max() else {
+        return;
+    };
+    let coeffs = &mut comm.poly.polynomial_mut().coeffs;
+    if coeffs.len() < end {
+        coeffs.resize(end, E::ScalarField::zero());
+    }
+    for (index, cell) in cells {
+        coeffs[*index] = *cell;
+    }
+    while coeffs.last().is_some_and(|c| c.is_zero()) {
+        coeffs.pop();
+    }
+    if comm.poly.degree_bound().is_some_and(|bound| bound < end) {
+        comm.poly = LabeledPolynomial::new(
+            comm.poly.label().clone(),
+            comm.poly.polynomial().clone(),
+            Some(end),
+            comm.poly.hiding_bound(),
+        );
+    }
+}
+
+/// a blinding polynomial for `hiding_bound` has `hiding_bound + 2` coefficients, committed with the powers of gamma_g
+fn check_gamma_powers<E: Pairing>(srs: &KZG10SRS<E>, hiding_bound: usize) -> Result<()> {
+    let needed = hiding_bound + 2;
+    if (0..needed).any(|i| !srs.powers_of_gamma_g.contains_key(&i)) {
+        return Err(anyhow!(
+            "hiding bound {} needs {} powers of gamma_g, the SRS has {}",
+            hiding_bound,
+            needed,
+            srs.powers_of_gamma_g.len()
+        ));
+    }
+    Ok(())
+}
+
 /// get `degree` number of powers from the universal params
-fn get_powers(
+pub(crate) fn get_powers<E: Pairing>(
     pp: &UniversalParams<E>,
     degree: usize,
-) -> Result<Powers<E>> {
+) -> Result<Powers<'_, E>> {
     let powers_of_g = pp.powers_of_g[..=degree].to_vec();
+    // a ceremony SRS has no powers of gamma_g, which are only needed for hiding commitments
     let powers_of_gamma_g = (0..=degree)
-        .map(|i| pp.powers_of_gamma_g[&i])
+        .map_while(|i| pp.powers_of_gamma_g.get(&i).copied())
         .collect();
     let powers = Powers {
         powers_of_g: ark_std::borrow::Cow::Owned(powers_of_g),
@@ -190,12 +513,12 @@ fn get_powers(
     Ok(powers)
 }
 
-pub fn get_vk(
+pub fn get_vk<E: Pairing>(
     pp: &UniversalParams<E>,
 ) -> Result<VerifierKey<E>> {
     let vk = VerifierKey {
         g: pp.powers_of_g[0],
-        gamma_g: pp.powers_of_gamma_g[&0],
+        gamma_g: pp.powers_of_gamma_g.get(&0).copied().unwrap_or_default(),
         h: pp.h,
         beta_h: pp.beta_h,
         prepared_h: pp.prepared_h.clone(),
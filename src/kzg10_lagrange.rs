@@ -0,0 +1,241 @@
+use ark_poly::univariate::DensePolynomial;
+use ark_poly::{DenseUVPolynomial, EvaluationDomain, GeneralEvaluationDomain};
+use ark_poly_commit::kzg10::{Commitment, Proof, VerifierKey};
+use ark_std::rand::{CryptoRng, RngCore};
+use anyhow::{anyhow, Result};
+use std::marker::PhantomData;
+use ark_bls12_381::Bls12_381;
+use ark_ec::pairing::Pairing;
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::Zero;
+use crate::kzg10::{check_distinct_cells, get_vk, KZG10CommitOutput, KZG10MultiProof, KZG10PolyComm, KZG10SRS};
+use crate::srs::SetupSource;
+use crate::traits::PolyCommScheme;
+
+/// KZG10 commitments to rows in evaluation (Lagrange) form over the pairing engine `E`:
+/// cell `j` of a row is the evaluation of the row polynomial at `w^j`, the j-th element of the row domain,
+/// so opening at `w^j` proves the stored cell and updating it adds a multiple of the j-th Lagrange basis commitment
+pub struct KZG10LagrangePolyComm<E: Pairing = Bls12_381> {
+    phantom_data: PhantomData<E>,
+}
+
+/// the KZG SRS with the commitments to the Lagrange basis of the row domain
+#[derive(Clone, Debug)]
+pub struct LagrangeSRS<E: Pairing = Bls12_381> {
+    pub srs: KZG10SRS<E>,
+    pub domain: GeneralEvaluationDomain<E::ScalarField>,
+    /// `lagrange_g[j]` = `L_j(beta) * g`
+    pub lagrange_g: Vec<E::G1Affine>,
+}
+
+/// the KZG verifier key with the row domain, needed to evaluate the Lagrange basis
+#[derive(Clone, Debug)]
+pub struct LagrangeVK<E: Pairing = Bls12_381> {
+    pub vk: VerifierKey<E>,
+    pub domain: GeneralEvaluationDomain<E::ScalarField>,
+}
+
+impl<E: Pairing> LagrangeSRS<E> {
+    /// derive the Lagrange basis for rows of `m` cells from a KZG SRS,
+    /// the row domain is the smallest supported domain with at least `m` elements
+    pub fn new(srs: KZG10SRS<E>, m: usize) -> Result<Self> {
+        let domain = GeneralEvaluationDomain::<E::ScalarField>::new(m).ok_or(anyhow!("polycommit domain error"))?;
+        // rows are interpolated to polynomials of degree < domain size, the extra power is for the KZG degree check
+        if srs.powers_of_g.len() <= domain.size() {
+            return Err(anyhow!(
+                "a row domain of size {} needs {} powers of g, the SRS has {}",
+                domain.size(),
+                domain.size() + 1,
+                srs.powers_of_g.len()
+            ));
+        }
+        // L_j(X) = 1/N sum_i w^{-ij} X^i, so the basis commitments are the inverse FFT of the powers of g
+        let powers: Vec<E::G1> = srs.powers_of_g[..domain.size()].iter().map(|p| (*p).into()).collect();
+        let lagrange_g = E::G1::normalize_batch(&domain.ifft(&powers));
+        Ok(Self{
+            srs,
+            domain,
+            lagrange_g,
+        })
+    }
+
+    pub fn vk(&self) -> Result<LagrangeVK<E>> {
+        Ok(LagrangeVK{
+            vk: get_vk(&self.srs)?,
+            domain: self.domain,
+        })
+    }
+
+    /// the point `w^j` at which the row polynomials evaluate to cell `j`
+    pub fn cell_point(&self, index: usize) -> E::ScalarField {
+        self.domain.element(index)
+    }
+
+    /// the row polynomial, in coefficient form, taking the values `row` over the domain
+    fn interpolate(&self, row: &[E::ScalarField]) -> Result<Vec<E::ScalarField>> {
+        if row.len() > self.domain.size() {
+            return Err(anyhow!("row of {} cells exceeds the domain size {}", row.len(), self.domain.size()));
+        }
+        let mut evals = row.to_vec();
+        evals.resize(self.domain.size(), E::ScalarField::zero());
+        Ok(self.domain.ifft(&evals))
+    }
+
+    fn check_index(&self, index: usize) -> Result<()> {
+        if index >= self.domain.size() {
+            return Err(anyhow!("cell index {} exceeds the domain size {}", index, self.domain.size()));
+        }
+        Ok(())
+    }
+}
+
+impl<E: Pairing> PolyCommScheme<E::ScalarField> for KZG10LagrangePolyComm<E> {
+    type SRS = LagrangeSRS<E>;
+    type VK = LagrangeVK<E>;
+    type CommitOutput = KZG10CommitOutput<E>;
+    type Comm = Commitment<E>;
+    type Proof = Proof<E>;
+    type MultiProof = KZG10MultiProof<E>;
+
+    /// `degree` is the number of cells in a row
+    fn setup(degree: usize, source: &SetupSource) -> Result<Self::SRS> {
+        let domain = GeneralEvaluationDomain::<E::ScalarField>::new(degree).ok_or(anyhow!("polycommit domain error"))?;
+        let srs = KZG10PolyComm::<E>::setup(domain.size(), source)?;
+        LagrangeSRS::new(srs, degree)
+    }
+
+    fn vk(srs: &Self::SRS) -> Result<Self::VK> {
+        srs.vk()
+    }
+
+    /// one cell per element of the row domain
+    fn max_cells(srs: &Self::SRS) -> usize {
+        srs.domain.size()
+    }
+
+    /// the input are the cells, the committed polynomial is their interpolation over the domain
+    fn commit(srs: &Self::SRS, input: Vec<E::ScalarField>) -> Result<Self::CommitOutput> {
+        KZG10PolyComm::<E>::commit(&srs.srs, srs.interpolate(&input)?)
+    }
+
+    fn commit_hiding<R: RngCore + CryptoRng>(srs: &Self::SRS, input: Vec<E::ScalarField>, hiding_bound: usize, rng: &mut R) -> Result<Self::CommitOutput> {
+        KZG10PolyComm::<E>::commit_hiding(&srs.srs, srs.interpolate(&input)?, hiding_bound, rng)
+    }
+
+    fn rerandomize<R: RngCore + CryptoRng>(srs: &Self::SRS, comm: &mut Self::CommitOutput, rng: &mut R) -> Result<()> {
+        KZG10PolyComm::<E>::rerandomize(&srs.srs, comm, rng)
+    }
+
+    fn update_commitment(srs: &Self::SRS, original_comm: &mut Self::CommitOutput, original_cell: E::ScalarField, new_cell: E::ScalarField, index: usize) -> Result<()> {
+        Self::update_commitment_batch(srs, original_comm, &[(index, original_cell, new_cell)])
+    }
+
+    /// the commitment is updated with a single MSM of the cell deltas against the Lagrange basis,
+    /// the polynomial with the interpolation of the deltas
+    fn update_commitment_batch(srs: &Self::SRS, original_comm: &mut Self::CommitOutput, cells: &[(usize, E::ScalarField, E::ScalarField)]) -> Result<()> {
+        check_distinct_cells(cells)?;
+        let mut bases = Vec::with_capacity(cells.len());
+        let mut deltas = Vec::with_capacity(cells.len());
+        let mut delta_evals = vec![E::ScalarField::zero(); srs.domain.size()];
+        for (index, original_cell, new_cell) in cells {
+            let delta = *new_cell - original_cell;
+            if delta == E::ScalarField::zero() {
+                continue;
+            }
+            srs.check_index(*index)?;
+            bases.push(srs.lagrange_g[*index]);
+            deltas.push(delta);
+            delta_evals[*index] += delta;
+        }
+        if deltas.is_empty() {
+            return Ok(())
+        }
+
+        // update the commitment
+        let delta_comm = <E as Pairing>::G1::msm(&bases, &deltas)
+            .map_err(|_| anyhow!("msm bases and scalars length mismatch"))?;
+        original_comm.comm.0 = (original_comm.comm.0 + delta_comm).into_affine();
+        // update the poly, the sum takes care of trailing zero coefficients
+        let delta_poly = DensePolynomial::from_coefficients_vec(srs.domain.ifft(&delta_evals));
+        *original_comm.poly.polynomial_mut() += &delta_poly;
+        // no update to rand, the blinding polynomial doesn't depend on the cells
+        Ok(())
+    }
+
+    fn open(
+        comm: &Self::CommitOutput,
+        srs: &Self::SRS,
+        point: E::ScalarField,
+    ) -> Result<Self::Proof> {
+        KZG10PolyComm::<E>::open(comm, &srs.srs, point)
+    }
+
+    fn verify(
+        vk:   &Self::VK,
+        comm: &Self::Comm,
+        point: E::ScalarField,
+        value: E::ScalarField,
+        proof: &Self::Proof,
+    ) -> Result<bool> {
+        KZG10PolyComm::<E>::verify(&vk.vk, comm, point, value, proof)
+    }
+
+    /// cell `index` multiplies the Lagrange polynomial `L_index(X) = w^index * Z(X) / (N * (X - w^index))`
+    fn cell_basis(vk: &Self::VK, index: usize, point: E::ScalarField) -> Result<E::ScalarField> {
+        if index >= vk.domain.size() {
+            return Err(anyhow!("cell index {} exceeds the domain size {}", index, vk.domain.size()));
+        }
+        let w_j = vk.domain.element(index);
+        if point == w_j {
+            return Ok(E::ScalarField::from(1u64));
+        }
+        let denominator = vk.domain.size_as_field_element() * (point - w_j);
+        Ok(w_j * vk.domain.evaluate_vanishing_polynomial(point) / denominator)
+    }
+
+    /// cell `index` is the evaluation at `w^index`
+    fn cell_point(vk: &Self::VK, index: usize) -> Result<E::ScalarField> {
+        if index >= vk.domain.size() {
+            return Err(anyhow!("cell index {} exceeds the domain size {}", index, vk.domain.size()));
+        }
+        Ok(vk.domain.element(index))
+    }
+
+    fn open_multi(
+        comm: &Self::CommitOutput,
+        srs: &Self::SRS,
+        points: &[E::ScalarField],
+    ) -> Result<(Vec<E::ScalarField>, Self::MultiProof)> {
+        KZG10PolyComm::<E>::open_multi(comm, &srs.srs, points)
+    }
+
+    fn verify_multi(
+        vk:   &Self::VK,
+        comm: &Self::Comm,
+        points: &[E::ScalarField],
+        values: &[E::ScalarField],
+        proof: &Self::MultiProof,
+    ) -> Result<bool> {
+        KZG10PolyComm::<E>::verify_multi(&vk.vk, comm, points, values, proof)
+    }
+
+    fn open_combination(
+        comms: &[&Self::CommitOutput],
+        coeffs: &[E::ScalarField],
+        srs: &Self::SRS,
+        point: E::ScalarField,
+    ) -> Result<Self::Proof> {
+        KZG10PolyComm::<E>::open_combination(comms, coeffs, &srs.srs, point)
+    }
+
+    fn verify_combination(
+        vk:   &Self::VK,
+        comms: &[&Self::Comm],
+        coeffs: &[E::ScalarField],
+        point: E::ScalarField,
+        value: E::ScalarField,
+        proof: &Self::Proof,
+    ) -> Result<bool> {
+        KZG10PolyComm::<E>::verify_combination(&vk.vk, comms, coeffs, point, value, proof)
+    }
+}
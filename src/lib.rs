@@ -1,7 +1,16 @@
 pub mod byte_data;
+pub mod fat_matrix;
 pub mod field_matrix;
 pub mod test;
 pub mod traits;
 pub mod encoder;
 pub mod kzg10;
-pub mod matrix_commit;
\ No newline at end of file
+pub mod kzg10_lagrange;
+pub mod matrix_commit;
+pub mod matrix_2d;
+pub mod merkle;
+pub mod protocol;
+pub mod serialization;
+pub mod srs;
+pub mod transcript;
+pub mod versioning;
\ No newline at end of file
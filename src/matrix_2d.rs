@@ -0,0 +1,197 @@
+use std::marker::PhantomData;
+use anyhow::{anyhow, Result};
+use ark_poly_commit::kzg10::{Commitment, Proof, VerifierKey};
+use ark_poly::EvaluationDomain;
+use ark_poly_commit::LabeledPolynomial;
+use crate::byte_data::Params;
+use crate::encoder::{BLSFieldEncoder, ColumnDomain};
+use crate::field_matrix::Matrix;
+use crate::kzg10::{get_powers, get_vk, KZG10CommitOutput, KZG10PolyComm, KZG10SRS, E, PCS};
+// the concrete scalar field rather than the `Pairing` projection, so the `P: PolyCommScheme<F>`
+// bounds of the impls below can be normalized
+use ark_bls12_381::Fr as F;
+use crate::matrix_commit::MatrixPolyComm;
+use crate::srs::SetupSource;
+use crate::traits::{CommitOutputTrait, DataMatrix, Encoder, MatrixCommitOutput, MatrixPolyCommScheme, PolyCommScheme};
+
+/// 2D commitments to an encoded field matrix: the rows are committed with `P`
+/// and every column is committed to its Reed–Solomon polynomial over the [`ColumnDomain`].
+/// The column SRS only has the powers for degree `< K` (the size of the interpolation subdomain),
+/// so a column commitment binds a codeword, and a cell is the column polynomial at the row point.
+/// The rows must be committed in evaluation form (e.g. `KZG10LagrangePolyComm`)
+/// so that the same cell can be opened against both its row and its column.
+pub struct Matrix2DPolyComm<P: PolyCommScheme<F>> {
+    phantom_data: PhantomData<P>,
+}
+
+pub struct Matrix2DSRS<P: PolyCommScheme<F>> {
+    pub row_srs: P::SRS,
+    pub col_srs: KZG10SRS,
+    pub domain: ColumnDomain,
+}
+
+pub struct Matrix2DVK<P: PolyCommScheme<F>> {
+    pub row_vk: P::VK,
+    pub col_vk: VerifierKey<E>,
+    pub domain: ColumnDomain,
+}
+
+/// the row and column commitments
+pub struct Matrix2DCommitOutput<P: PolyCommScheme<F>> {
+    pub rows: MatrixCommitOutput<F, P>,
+    pub cols: Vec<KZG10CommitOutput>,
+}
+
+impl<P: PolyCommScheme<F>> Clone for Matrix2DCommitOutput<P> where P::CommitOutput: Clone {
+    fn clone(&self) -> Self {
+        Self{
+            rows: self.rows.clone(),
+            cols: self.cols.clone(),
+        }
+    }
+}
+
+impl<P: PolyCommScheme<F>> Matrix2DCommitOutput<P> {
+    pub fn get_row_comm(&self, row: usize) -> &<P::CommitOutput as CommitOutputTrait>::Comm {
+        self.rows.get_comm(row)
+    }
+
+    pub fn get_col_comm(&self, col: usize) -> &Commitment<E> {
+        &self.cols[col].comm
+    }
+}
+
+/// the value of cell `(row, col)` opened against both its row and its column commitment
+pub struct CellConsistencyProof<P: PolyCommScheme<F>> {
+    pub row: usize,
+    pub col: usize,
+    pub value: F,
+    /// opening of the row at the cell point of `col`
+    pub row_proof: P::Proof,
+    /// opening of the column at the row point of `row`
+    pub col_proof: Proof<E>,
+}
+
+impl<P: PolyCommScheme<F>> Clone for CellConsistencyProof<P> where P::Proof: Clone {
+    fn clone(&self) -> Self {
+        Self{
+            row: self.row,
+            col: self.col,
+            value: self.value,
+            row_proof: self.row_proof.clone(),
+            col_proof: self.col_proof,
+        }
+    }
+}
+
+impl<P: PolyCommScheme<F>> Matrix2DPolyComm<P> {
+    /// setup for matrices with the given params: the rows have `m` cells,
+    /// the columns are polynomials of degree `< K`
+    pub fn setup(params: &Params, source: &SetupSource) -> Result<Matrix2DSRS<P>> {
+        let domain = ColumnDomain::new(params.k, params.n)?;
+        let row_srs = MatrixPolyComm::<F, P>::setup(params.m, source)?;
+        let col_srs = KZG10PolyComm::setup(domain.data_domain.size() - 1, source)?;
+        Ok(Matrix2DSRS{
+            row_srs,
+            col_srs,
+            domain,
+        })
+    }
+
+    pub fn vk(srs: &Matrix2DSRS<P>) -> Result<Matrix2DVK<P>> {
+        Ok(Matrix2DVK{
+            row_vk: P::vk(&srs.row_srs)?,
+            col_vk: get_vk(&srs.col_srs)?,
+            domain: srs.domain.clone(),
+        })
+    }
+
+    /// commit to the rows and the columns of an encoded matrix
+    pub fn commit(srs: &Matrix2DSRS<P>, matrix: &Matrix<F>) -> Result<Matrix2DCommitOutput<P>> {
+        check_domain(srs, &matrix.params)?;
+        let rows = MatrixPolyComm::<F, P>::commit(&srs.row_srs, matrix)?;
+        let cols = (0..matrix.params.m)
+            .map(|c| Self::commit_col(srs, &matrix.get_col(c)?))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Matrix2DCommitOutput{
+            rows,
+            cols,
+        })
+    }
+
+    /// commit to the polynomial of an encoded column, fails if the column is not a codeword
+    pub fn commit_col(srs: &Matrix2DSRS<P>, col: &[F]) -> Result<KZG10CommitOutput> {
+        let domain = &srs.domain;
+        let params = Params{ k: domain.k, n: domain.n, m: 1 };
+        if !BLSFieldEncoder::is_codeword(params, col)? {
+            return Err(anyhow!("the column is not a codeword"));
+        }
+        let poly = domain.interpolate(&col[..domain.k])?;
+        let degree = domain.data_domain.size() - 1;
+        let powers = get_powers(&srs.col_srs, degree)?;
+        let labeled_poly = LabeledPolynomial::new(String::from("col_poly"), poly, Some(degree), None);
+        let (comm, rand) = PCS::commit(&powers, &labeled_poly, None, None)?;
+        Ok(KZG10CommitOutput::new(labeled_poly, comm, rand))
+    }
+
+    /// open cell `(row, col)` against its row and its column
+    pub fn open_cell(srs: &Matrix2DSRS<P>, comm: &Matrix2DCommitOutput<P>, row: usize, col: usize) -> Result<CellConsistencyProof<P>> {
+        let row_output = comm.rows.comm_output.get(row).ok_or(anyhow!("row index {} out of bounds", row))?;
+        let col_output = comm.cols.get(col).ok_or(anyhow!("col index {} out of bounds", col))?;
+
+        let cell_point = P::cell_point(&P::vk(&srs.row_srs)?, col)?;
+        let row_point = srs.domain.row_point(row);
+        let value = col_output.poly.evaluate(&row_point);
+        Ok(CellConsistencyProof{
+            row,
+            col,
+            value,
+            row_proof: P::open(row_output, &srs.row_srs, cell_point)?,
+            col_proof: KZG10PolyComm::open(col_output, &srs.col_srs, row_point)?,
+        })
+    }
+
+    /// check the cell opens to the same value in its row and its column
+    pub fn verify_cell(
+        vk: &Matrix2DVK<P>,
+        row_comm: &P::Comm,
+        col_comm: &Commitment<E>,
+        proof: &CellConsistencyProof<P>,
+    ) -> Result<bool> {
+        if proof.row >= vk.domain.n {
+            return Ok(false);
+        }
+        let cell_point = P::cell_point(&vk.row_vk, proof.col)?;
+        let row_point = vk.domain.row_point(proof.row);
+        Ok(P::verify(&vk.row_vk, row_comm, cell_point, proof.value, &proof.row_proof)?
+            && KZG10PolyComm::verify(&vk.col_vk, col_comm, row_point, proof.value, &proof.col_proof)?)
+    }
+
+    /// refresh the commitments after `update_col`: the rows are updated with the column delta
+    /// and the commitment of the one affected column is recomputed
+    pub fn update_col(
+        srs: &Matrix2DSRS<P>,
+        comm: &mut Matrix2DCommitOutput<P>,
+        col_idx: usize,
+        old_col: &[F],
+        new_col: &[F],
+    ) -> Result<()> {
+        if col_idx >= comm.cols.len() {
+            return Err(anyhow!("col index {} out of bounds", col_idx));
+        }
+        let col_output = Self::commit_col(srs, new_col)?;
+        MatrixPolyComm::<F, P>::update_commitments(&srs.row_srs, &mut comm.rows, col_idx, old_col, new_col)?;
+        comm.cols[col_idx] = col_output;
+        Ok(())
+    }
+}
+
+fn check_domain<P: PolyCommScheme<F>>(srs: &Matrix2DSRS<P>, params: &Params) -> Result<()> {
+    if srs.domain.k != params.k || srs.domain.n != params.n {
+        return Err(anyhow!(
+            "the setup is for k = {}, n = {} but the matrix has k = {}, n = {}",
+            srs.domain.k, srs.domain.n, params.k, params.n
+        ));
+    }
+    Ok(())
+}
@@ -1,28 +1,90 @@
 use std::marker::PhantomData;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use ark_ff::Field;
+use ark_std::{cfg_into_iter, cfg_iter_mut};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use ark_std::rand::{CryptoRng, RngCore};
+use crate::fat_matrix::FatMatrix;
+use crate::srs::SetupSource;
 use crate::field_matrix::Matrix;
-use crate::traits::{MatrixPolyCommScheme, DataMatrix, PolyCommScheme, MatrixCommitOutput};
+use crate::byte_data::Params;
+use crate::traits::{MatrixPolyCommScheme, DataMatrix, PolyCommScheme, MatrixCommitOutput, CellProof, ColumnUpdate, Encoder, UpdateProof};
 
 
 pub struct MatrixPolyComm<F, P: PolyCommScheme<F>> {
     phantom_data: PhantomData<(F,P)>
 }
 
+/// commitments to fat cell matrices, each row is committed over the lanes of all its cells
+/// so cell (r,c) lane l is coefficient `c * cell_size + l` of the row polynomial
+impl<F: Field + Clone, P: PolyCommScheme<F>> MatrixPolyComm<F, P> {
+
+    /// setup for a fat cell matrix with `m` columns of `cell_size` lanes
+    pub fn setup_fat(m: usize, cell_size: usize, source: &SetupSource) -> Result<P::SRS> {
+        P::setup(m * cell_size, source)
+    }
+
+    pub fn commit_fat(srs: &P::SRS, matrix: &FatMatrix<F>) -> Result<MatrixCommitOutput<F, P>> {
+        let row_comm_output = cfg_into_iter!(0..matrix.params.n)
+            .map(|i| P::commit(srs, matrix.get_flat_row(i)?.to_vec()))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(
+            MatrixCommitOutput::new(row_comm_output)
+        )
+    }
+
+    /// updates the row commitments after updating a fat column,
+    /// the lanes of the column are applied as one batch of single element columns
+    pub fn update_fat_commitments(
+        srs: &P::SRS,
+        comm: &mut MatrixCommitOutput<F, P>,
+        cell_size: usize,
+        col_idx: usize,
+        old_col: &[Vec<F>],
+        new_col: &[Vec<F>],
+    ) -> Result<()> {
+        // check input is consistent
+        assert_eq!(old_col.len(), new_col.len(), "col sizes don't match");
+
+        let updates: Vec<ColumnUpdate<F>> = (0..cell_size)
+            .map(|l| ColumnUpdate::new(
+                col_idx * cell_size + l,
+                old_col.iter().map(|cell| cell[l]).collect(),
+                new_col.iter().map(|cell| cell[l]).collect(),
+            ))
+            .collect();
+
+        Self::update_commitments_batch(srs, comm, &updates)
+    }
+}
+
 impl<F: Field + Clone, P: PolyCommScheme<F>> MatrixPolyCommScheme<F, P> for MatrixPolyComm<F, P> {
     type FieldMatrix = Matrix<F>;
 
     /// setup takes `m`=`number of columns` in the matrix
-    fn setup(m: usize) -> Result<P::SRS> {
-        P::setup(m)
+    fn setup(m: usize, source: &SetupSource) -> Result<P::SRS> {
+        P::setup(m, source)
     }
 
+    /// the rows are committed in parallel with the `parallel` feature
     fn commit(srs: &P::SRS, matrix: &Self::FieldMatrix) -> Result<MatrixCommitOutput<F, P>> {
 
+        let row_comm_output = cfg_into_iter!(0..matrix.params.n)
+            .map(|i| P::commit(srs, matrix.get_row(i)?))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(
+            MatrixCommitOutput::new(row_comm_output)
+        )
+    }
+
+    fn commit_hiding<R: RngCore + CryptoRng>(srs: &P::SRS, matrix: &Self::FieldMatrix, hiding_bound: usize, rng: &mut R) -> Result<MatrixCommitOutput<F, P>> {
         let mut row_comm_output = vec![];
         for i in 0..matrix.params.n{
             let row = matrix.get_row(i)?;
-            let output = P::commit(srs,row)?;
+            let output = P::commit_hiding(srs, row, hiding_bound, rng)?;
             row_comm_output.push(output);
         }
 
@@ -31,9 +93,17 @@ impl<F: Field + Clone, P: PolyCommScheme<F>> MatrixPolyCommScheme<F, P> for Matr
         )
     }
 
+    fn rerandomize<R: RngCore + CryptoRng>(srs: &P::SRS, comm: &mut MatrixCommitOutput<F, P>, rows: &[usize], rng: &mut R) -> Result<()> {
+        for &row in rows {
+            let output = comm.comm_output.get_mut(row)
+                .ok_or(anyhow!("row index {} out of bounds", row))?;
+            P::rerandomize(srs, output, rng)?;
+        }
+        Ok(())
+    }
+
     /// updates the row commitments after updating/modifying columns
-    /// since the data DataMatrix should only allow column updates
-    /// and since we commit to rows
+    /// since we commit to rows, a column update changes one cell of every row
     /// this means we update all row commitments that are affected by the data matrix update
     fn update_commitments(
         srs: &P::SRS,
@@ -47,13 +117,83 @@ impl<F: Field + Clone, P: PolyCommScheme<F>> MatrixPolyCommScheme<F, P> for Matr
 
         // loop through all new_col elements to see if there is an update at each cell
         // if there is, then update the commitment
-        for r in 0..new_col.len(){
-            let original_cell: F = old_col[r].clone();
-            let new_cell: F = new_col[r].clone();
-            P::update_commitment(srs, &mut comm.comm_output[r], original_cell, new_cell, col_idx)?;
+        cfg_iter_mut!(comm.comm_output[..new_col.len()])
+            .enumerate()
+            .try_for_each(|(r, row_comm)| P::update_commitment(srs, row_comm, old_col[r], new_col[r], col_idx))
+    }
+
+    /// updates the row commitments after updating several columns,
+    /// every row commitment is updated once with all of its changed cells
+    fn update_commitments_batch(
+        srs: &P::SRS,
+        comm: &mut MatrixCommitOutput<F, P>,
+        updates: &[ColumnUpdate<F>],
+    ) -> Result<()> {
+        // check input is consistent
+        let rows = comm.comm_output.len();
+        for update in updates {
+            assert_eq!(update.old_col.len(), update.new_col.len(), "col sizes don't match");
+            assert_eq!(update.new_col.len(), rows, "col size doesn't match the number of rows");
         }
 
-        Ok(())
+        cfg_iter_mut!(comm.comm_output)
+            .enumerate()
+            .try_for_each(|(r, row_comm)| {
+                let cells: Vec<(usize, F, F)> = updates
+                    .iter()
+                    .map(|u| (u.col_idx, u.old_col[r], u.new_col[r]))
+                    .collect();
+                P::update_commitment_batch(srs, row_comm, &cells)
+            })
+    }
+
+    /// the appended cells grow from zero, so this is a batch update of the new columns
+    fn append_commitments(
+        srs: &P::SRS,
+        comm: &mut MatrixCommitOutput<F, P>,
+        first_col: usize,
+        new_cols: &[Vec<F>],
+    ) -> Result<()> {
+        let end = first_col + new_cols.len();
+        if end > P::max_cells(srs) {
+            return Err(anyhow!("{} columns exceed the srs size of {} cells", end, P::max_cells(srs)));
+        }
+        let zeros = vec![F::zero(); comm.comm_output.len()];
+        let updates: Vec<ColumnUpdate<F>> = new_cols.iter().enumerate()
+            .map(|(i, col)| ColumnUpdate::new(first_col + i, zeros.clone(), col.clone()))
+            .collect();
+        Self::update_commitments_batch(srs, comm, &updates)
+    }
+
+    /// the removed cells are set back to zero
+    fn truncate_commitments(
+        srs: &P::SRS,
+        comm: &mut MatrixCommitOutput<F, P>,
+        first_col: usize,
+        old_cols: &[Vec<F>],
+    ) -> Result<()> {
+        let zeros = vec![F::zero(); comm.comm_output.len()];
+        let updates: Vec<ColumnUpdate<F>> = old_cols.iter().enumerate()
+            .map(|(i, col)| ColumnUpdate::new(first_col + i, col.clone(), zeros.clone()))
+            .collect();
+        Self::update_commitments_batch(srs, comm, &updates)
+    }
+
+    /// every cell from `col_idx` on takes the value of the next column and the last one becomes zero
+    fn delete_col_commitments(
+        srs: &P::SRS,
+        comm: &mut MatrixCommitOutput<F, P>,
+        col_idx: usize,
+        old_cols: &[Vec<F>],
+    ) -> Result<()> {
+        let zeros = vec![F::zero(); comm.comm_output.len()];
+        let updates: Vec<ColumnUpdate<F>> = old_cols.iter().enumerate()
+            .map(|(i, col)| {
+                let next = old_cols.get(i + 1).unwrap_or(&zeros);
+                ColumnUpdate::new(col_idx + i, col.clone(), next.clone())
+            })
+            .collect();
+        Self::update_commitments_batch(srs, comm, &updates)
     }
 
     fn open(comm: &MatrixCommitOutput<F, P>, srs: &P::SRS, row: usize, point: F) -> Result<P::Proof> {
@@ -71,14 +211,185 @@ impl<F: Field + Clone, P: PolyCommScheme<F>> MatrixPolyCommScheme<F, P> for Matr
         proof: &P::Proof,
     ) -> Result<bool> {
 
-        Ok( P::verify(
-            &vk,
+        P::verify(
+            vk,
             comm,
             point,
             value,
             proof,
-        )? )
+        )
+    }
+
+    /// the aggregated proof opens `sum_i challenge^i * row_i` at `point`
+    fn batch_open(
+        comm: &MatrixCommitOutput<F, P>,
+        srs: &P::SRS,
+        rows: &[usize],
+        point: F,
+        challenge: F,
+    ) -> Result<P::Proof> {
+        if let Some(&row) = rows.iter().find(|&&row| row >= comm.comm_output.len()) {
+            return Err(anyhow!("row index {} out of bounds; must be < {}", row, comm.comm_output.len()));
+        }
+        let row_comms: Vec<&P::CommitOutput> = rows.iter().map(|&r| &comm.comm_output[r]).collect();
+        let coeffs = challenge_powers(challenge, rows.len());
+
+        P::open_combination(&row_comms, &coeffs, srs, point)
+    }
+
+    /// checks the aggregated proof against `sum_i challenge^i * comm_i` and `sum_i challenge^i * value_i`
+    /// so the number of pairings is constant in the number of rows
+    fn batch_verify(
+        vk:   &P::VK,
+        comms: &[&P::Comm],
+        point: F,
+        values: &[F],
+        challenge: F,
+        proof: &P::Proof,
+    ) -> Result<bool> {
+        if comms.len() != values.len() {
+            return Ok(false);
+        }
+        let coeffs = challenge_powers(challenge, comms.len());
+        let value = coeffs.iter().zip(values).map(|(c, v)| *c * v).sum();
+
+        P::verify_combination(vk, comms, &coeffs, point, value, proof)
     }
 
+    /// the cells are grouped by row, in the order the rows first appear, repeated cells are opened once
+    fn open_cells(
+        comm: &MatrixCommitOutput<F, P>,
+        srs: &P::SRS,
+        cells: &[(usize, usize)],
+    ) -> Result<Vec<CellProof<F, P>>> {
+        let mut rows: Vec<(usize, Vec<usize>)> = vec![];
+        for &(row, col) in cells {
+            if row >= comm.comm_output.len() {
+                return Err(anyhow!("row index {} out of bounds", row));
+            }
+            match rows.iter_mut().find(|(r, _)| *r == row) {
+                Some((_, cols)) if cols.contains(&col) => {}
+                Some((_, cols)) => cols.push(col),
+                None => rows.push((row, vec![col])),
+            }
+        }
+
+        let vk = P::vk(srs)?;
+        let points = rows.iter()
+            .map(|(_, cols)| cols.iter().map(|&c| P::cell_point(&vk, c)).collect::<Result<Vec<F>>>())
+            .collect::<Result<Vec<_>>>()?;
+        // the rows are opened in parallel with the `parallel` feature
+        cfg_into_iter!(rows)
+            .zip(points)
+            .map(|((row, cols), points)| {
+                let (values, proof) = P::open_multi(&comm.comm_output[row], srs, &points)?;
+                Ok(CellProof{
+                    row,
+                    cols,
+                    values,
+                    proof,
+                })
+            })
+            .collect()
+    }
+
+    fn verify_cells(
+        vk:   &P::VK,
+        comms: &[&P::Comm],
+        proofs: &[CellProof<F, P>],
+    ) -> Result<bool> {
+        for cell_proof in proofs {
+            let Some(comm) = comms.get(cell_proof.row) else {
+                return Ok(false);
+            };
+            if cell_proof.cols.len() != cell_proof.values.len() {
+                return Ok(false);
+            }
+            let points = cell_proof.cols.iter().map(|&c| P::cell_point(vk, c)).collect::<Result<Vec<F>>>()?;
+            if !P::verify_multi(vk, comm, &points, &cell_proof.values, &cell_proof.proof)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// since every row changes only at cell `col_idx`, `new_row_r - old_row_r = delta_r * B(X)`
+    /// where `B` is the basis polynomial of that cell, the proof opens the combination of these
+    /// differences which must evaluate to `B(point) * sum_r challenge^r * delta_r`
+    fn prove_update(
+        srs: &P::SRS,
+        old_comm: &MatrixCommitOutput<F, P>,
+        new_comm: &MatrixCommitOutput<F, P>,
+        update: &ColumnUpdate<F>,
+        point: F,
+        challenge: F,
+    ) -> Result<UpdateProof<F, P>> {
+        // check input is consistent
+        let rows = old_comm.comm_output.len();
+        assert_eq!(new_comm.comm_output.len(), rows, "number of rows doesn't match");
+        assert_eq!(update.old_col.len(), rows, "old col size doesn't match the number of rows");
+        assert_eq!(update.new_col.len(), rows, "new col size doesn't match the number of rows");
+
+        let delta: Vec<F> = update.new_col.iter().zip(&update.old_col).map(|(n, o)| *n - o).collect();
+
+        let (comms, coeffs) = update_combination(
+            new_comm.comm_output.iter().collect(),
+            old_comm.comm_output.iter().collect(),
+            challenge,
+        );
+        let proof = P::open_combination(&comms, &coeffs, srs, point)?;
+
+        Ok(UpdateProof{
+            col_idx: update.col_idx,
+            delta,
+            proof,
+        })
+    }
+
+    fn verify_update<Enc: Encoder<F>>(
+        vk:   &P::VK,
+        params: &Params,
+        old_comms: &[&P::Comm],
+        new_comms: &[&P::Comm],
+        proof: &UpdateProof<F, P>,
+        point: F,
+        challenge: F,
+    ) -> Result<bool> {
+        let rows = params.n;
+        if old_comms.len() != rows || new_comms.len() != rows || proof.delta.len() != rows {
+            return Ok(false);
+        }
+        params.check_cols(proof.col_idx)?;
+
+        // the old column is a codeword, so the new one is iff the delta is
+        if !Enc::is_codeword(params.clone(), &proof.delta)? {
+            return Ok(false);
+        }
+
+        let (comms, coeffs) = update_combination(new_comms.to_vec(), old_comms.to_vec(), challenge);
+        let delta_sum: F = challenge_powers(challenge, rows).iter().zip(&proof.delta).map(|(c, d)| *c * d).sum();
+        let value = P::cell_basis(vk, proof.col_idx, point)? * delta_sum;
+
+        P::verify_combination(vk, &comms, &coeffs, point, value, &proof.proof)
+    }
+
+}
+
+/// the first `len` powers of `challenge`: `1, challenge, challenge^2, ...`
+fn challenge_powers<F: Field>(challenge: F, len: usize) -> Vec<F> {
+    let mut powers = Vec::with_capacity(len);
+    let mut cur = F::one();
+    for _ in 0..len {
+        powers.push(cur);
+        cur *= challenge;
+    }
+    powers
 }
 
+/// the items and coefficients of `sum_r challenge^r * (new_r - old_r)`
+fn update_combination<'a, F: Field, T>(new: Vec<&'a T>, old: Vec<&'a T>, challenge: F) -> (Vec<&'a T>, Vec<F>) {
+    let powers = challenge_powers(challenge, new.len());
+    let coeffs = powers.iter().copied().chain(powers.iter().map(|c| -*c)).collect();
+    let items = new.into_iter().chain(old).collect();
+    (items, coeffs)
+}
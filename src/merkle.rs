@@ -0,0 +1,254 @@
+use anyhow::{anyhow, Result};
+use ark_crypto_primitives::sponge::poseidon::{find_poseidon_ark_and_mds, PoseidonConfig, PoseidonSponge};
+use ark_crypto_primitives::sponge::{CryptographicSponge, FieldBasedCryptographicSponge};
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use sha2::{Digest, Sha256};
+use crate::kzg10::F;
+
+/// Hash function used to build the Merkle tree
+pub trait MerkleHasher {
+    type Digest: Clone + PartialEq + std::fmt::Debug;
+
+    /// hash a leaf given as bytes
+    fn hash_leaf(&self, leaf: &[u8]) -> Self::Digest;
+    /// hash two children into their parent node
+    fn hash_nodes(&self, left: &Self::Digest, right: &Self::Digest) -> Self::Digest;
+}
+
+// ------------- SHA-256 ------------
+
+/// domain separation between leaves and inner nodes
+const LEAF_TAG: u8 = 0;
+const NODE_TAG: u8 = 1;
+
+#[derive(Clone, Debug, Default)]
+pub struct Sha256Hasher;
+
+impl MerkleHasher for Sha256Hasher {
+    type Digest = [u8; 32];
+
+    fn hash_leaf(&self, leaf: &[u8]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update([LEAF_TAG]);
+        hasher.update(leaf);
+        hasher.finalize().into()
+    }
+
+    fn hash_nodes(&self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update([NODE_TAG]);
+        hasher.update(left);
+        hasher.update(right);
+        hasher.finalize().into()
+    }
+}
+
+// ------------- Poseidon ------------
+
+/// Poseidon over the scalar field `F` with width 3 (rate 2, capacity 1) and alpha = 5
+#[derive(Clone, Debug)]
+pub struct PoseidonHasher {
+    pub config: PoseidonConfig<F>,
+}
+
+impl Default for PoseidonHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PoseidonHasher {
+    pub fn new() -> Self {
+        let full_rounds = 8;
+        let partial_rounds = 57;
+        let rate = 2;
+        let (ark, mds) = find_poseidon_ark_and_mds::<F>(
+            F::MODULUS_BIT_SIZE as u64,
+            rate,
+            full_rounds,
+            partial_rounds,
+            0,
+        );
+        Self{
+            config: PoseidonConfig::new(full_rounds as usize, partial_rounds as usize, 5, mds, ark, rate, 1),
+        }
+    }
+}
+
+impl MerkleHasher for PoseidonHasher {
+    type Digest = F;
+
+    fn hash_leaf(&self, leaf: &[u8]) -> F {
+        let mut sponge = PoseidonSponge::new(&self.config);
+        sponge.absorb(&F::from(LEAF_TAG));
+        sponge.absorb(&leaf);
+        sponge.squeeze_native_field_elements(1)[0]
+    }
+
+    fn hash_nodes(&self, left: &F, right: &F) -> F {
+        let mut sponge = PoseidonSponge::new(&self.config);
+        sponge.absorb(&F::from(NODE_TAG));
+        sponge.absorb(&vec![*left, *right]);
+        sponge.squeeze_native_field_elements(1)[0]
+    }
+}
+
+// ------------- Merkle Tree ------------
+
+/// Merkle tree over the serialized row commitments,
+/// the root serves as the dataset identifier.
+/// a node without a sibling (last node of an odd level) is promoted to the next level as is.
+pub struct MerkleTree<H: MerkleHasher> {
+    pub hasher: H,
+    /// `levels[0]` are the leaf hashes and the last level holds the root
+    levels: Vec<Vec<H::Digest>>,
+}
+
+/// inclusion proof of the leaf at `index`, the siblings are given from the leaf level up,
+/// `None` when the node was promoted without a sibling
+#[derive(Clone, Debug, PartialEq)]
+pub struct MerkleProof<D> {
+    pub index: usize,
+    pub siblings: Vec<Option<D>>,
+}
+
+impl<H: MerkleHasher> MerkleTree<H> {
+    /// build the tree from the leaves given as bytes
+    pub fn new(hasher: H, leaves: &[Vec<u8>]) -> Result<Self> {
+        if leaves.is_empty() {
+            return Err(anyhow!("cannot build a Merkle tree without leaves"));
+        }
+        let leaf_hashes: Vec<H::Digest> = leaves.iter().map(|l| hasher.hash_leaf(l)).collect();
+        let mut levels = vec![leaf_hashes];
+        while levels.last().unwrap().len() > 1 {
+            let level = levels.last().unwrap();
+            let next = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hasher.hash_nodes(left, right),
+                    [single] => single.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        Ok(Self{
+            hasher,
+            levels,
+        })
+    }
+
+    /// build the tree from the row commitments, each leaf is the compressed serialization of a commitment
+    pub fn from_commitments<C: CanonicalSerialize>(hasher: H, comms: &[C]) -> Result<Self> {
+        let leaves = comms.iter().map(serialize_leaf).collect::<Result<Vec<_>>>()?;
+        Self::new(hasher, &leaves)
+    }
+
+    pub fn root(&self) -> H::Digest {
+        self.levels.last().unwrap()[0].clone()
+    }
+
+    pub fn num_leaves(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// inclusion proof for the leaf at `index`
+    pub fn prove(&self, index: usize) -> Result<MerkleProof<H::Digest>> {
+        if index >= self.num_leaves() {
+            return Err(anyhow!("leaf index {} out of bounds; must be < {}", index, self.num_leaves()));
+        }
+        let mut siblings = Vec::with_capacity(self.levels.len() - 1);
+        let mut idx = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            siblings.push(level.get(idx ^ 1).cloned());
+            idx /= 2;
+        }
+        Ok(MerkleProof{
+            index,
+            siblings,
+        })
+    }
+
+    /// verify the inclusion proof of `leaf` against `root` of a tree with `num_leaves` leaves,
+    /// the proof must have a sibling exactly where the tree has one
+    pub fn verify(hasher: &H, root: &H::Digest, num_leaves: usize, leaf: &[u8], proof: &MerkleProof<H::Digest>) -> bool {
+        if proof.index >= num_leaves {
+            return false;
+        }
+        let mut node = hasher.hash_leaf(leaf);
+        let mut idx = proof.index;
+        // number of nodes in the current level
+        let mut len = num_leaves;
+        for sibling in &proof.siblings {
+            if len == 1 {
+                return false;
+            }
+            match (sibling, idx ^ 1 < len) {
+                (Some(sibling), true) => {
+                    node = if idx % 2 == 0 {
+                        hasher.hash_nodes(&node, sibling)
+                    } else {
+                        hasher.hash_nodes(sibling, &node)
+                    };
+                }
+                (None, false) => {}
+                _ => return false,
+            }
+            idx /= 2;
+            len = len.div_ceil(2);
+        }
+        len == 1 && &node == root
+    }
+
+    /// verify the inclusion proof of a row commitment against `root` of a tree with `num_leaves` leaves
+    pub fn verify_commitment<C: CanonicalSerialize>(hasher: &H, root: &H::Digest, num_leaves: usize, comm: &C, proof: &MerkleProof<H::Digest>) -> Result<bool> {
+        Ok(Self::verify(hasher, root, num_leaves, &serialize_leaf(comm)?, proof))
+    }
+
+    /// replace the given leaves and recompute only the nodes on their paths to the root
+    pub fn update_leaves(&mut self, leaves: &[(usize, Vec<u8>)]) -> Result<()> {
+        let mut dirty = Vec::with_capacity(leaves.len());
+        for (index, leaf) in leaves {
+            if *index >= self.num_leaves() {
+                return Err(anyhow!("leaf index {} out of bounds; must be < {}", index, self.num_leaves()));
+            }
+            self.levels[0][*index] = self.hasher.hash_leaf(leaf);
+            dirty.push(*index);
+        }
+
+        for l in 1..self.levels.len() {
+            dirty = dirty.iter().map(|i| i / 2).collect();
+            dirty.sort_unstable();
+            dirty.dedup();
+            let (lower, upper) = self.levels.split_at_mut(l);
+            let children = &lower[l - 1];
+            for &i in &dirty {
+                upper[0][i] = match children.get(2 * i + 1) {
+                    Some(right) => self.hasher.hash_nodes(&children[2 * i], right),
+                    None => children[2 * i].clone(),
+                };
+            }
+        }
+        Ok(())
+    }
+
+    /// refresh the leaves of the changed `rows` from the (updated) row commitments `comms`
+    pub fn update_commitments<C: CanonicalSerialize>(&mut self, rows: &[usize], comms: &[C]) -> Result<()> {
+        let leaves = rows
+            .iter()
+            .map(|&r| {
+                let comm = comms.get(r).ok_or(anyhow!("no commitment for row {}", r))?;
+                Ok((r, serialize_leaf(comm)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.update_leaves(&leaves)
+    }
+}
+
+/// leaf bytes of a commitment
+fn serialize_leaf<C: CanonicalSerialize>(comm: &C) -> Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(comm.compressed_size());
+    comm.serialize_compressed(&mut bytes)?;
+    Ok(bytes)
+}
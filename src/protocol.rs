@@ -0,0 +1,402 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+use anyhow::{anyhow, Result};
+use ark_poly_commit::kzg10::{Commitment, Proof, VerifierKey};
+use ark_std::rand::{rngs::StdRng, Rng, SeedableRng};
+use ark_std::{cfg_iter, UniformRand};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use crate::byte_data::Params;
+use crate::encoder::{changed_cells, BLSFieldEncoder};
+use crate::field_matrix::Matrix;
+use crate::kzg10::{get_vk, KZG10PolyComm, KZG10SRS, E, F};
+use crate::matrix_commit::MatrixPolyComm;
+use crate::merkle::{MerkleTree, Sha256Hasher};
+use crate::transcript::Transcript;
+use crate::traits::{ColumnUpdate, CommitOutputTrait, DataMatrix, Encoder, MatrixCommitOutput, MatrixPolyCommScheme, UpdateProof};
+
+type P = KZG10PolyComm;
+type C = MatrixPolyComm<F, P>;
+
+/// domain separator of the storage challenge transcript
+const CHALLENGE_LABEL: &[u8] = b"dde-storage-challenge";
+
+// ------------- Messages ------------
+
+/// sampling challenge: a set of rows and an evaluation point, derived from a seed
+#[derive(Clone, Debug, PartialEq)]
+pub struct Challenge {
+    pub seed: u64,
+    pub rows: Vec<usize>,
+    pub point: F,
+}
+
+impl Challenge {
+    /// derive `num_rows` distinct rows out of `n` and the evaluation point from `seed`
+    pub fn from_seed(seed: u64, n: usize, num_rows: usize) -> Result<Self> {
+        if num_rows > n {
+            return Err(anyhow!("cannot sample {} distinct rows out of {}", num_rows, n));
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut rows = Vec::with_capacity(num_rows);
+        while rows.len() < num_rows {
+            let row = rng.gen_range(0..n);
+            if !rows.contains(&row) {
+                rows.push(row);
+            }
+        }
+        let point = F::rand(&mut rng);
+        Ok(Self{
+            seed,
+            rows,
+            point,
+        })
+    }
+
+    /// derive the challenge non-interactively from public data: the Merkle `root` of the row commitments,
+    /// the epoch/block `randomness` and a `nonce`, so anyone holding them derives the same rows and point.
+    /// the nonce is kept as the seed
+    pub fn derive(root: &[u8], randomness: &[u8], nonce: u64, n: usize, num_rows: usize) -> Result<Self> {
+        let mut transcript = Transcript::new(CHALLENGE_LABEL);
+        transcript.append_message(b"root", root);
+        transcript.append_message(b"randomness", randomness);
+        transcript.append_u64(b"nonce", nonce);
+        transcript.append_u64(b"n", n as u64);
+        transcript.append_u64(b"num_rows", num_rows as u64);
+        let rows = transcript.challenge_indices(b"rows", n, num_rows)?;
+        let point = transcript.challenge_field(b"point");
+        Ok(Self{
+            seed: nonce,
+            rows,
+            point,
+        })
+    }
+}
+
+/// the Sha256 Merkle root of the row commitments, the public handle of the stored data
+pub fn commitments_root(comms: &[Commitment<E>]) -> Result<[u8; 32]> {
+    Ok(MerkleTree::from_commitments(Sha256Hasher, comms)?.root())
+}
+
+/// verify the response to a challenge against the row commitments
+pub fn verify_response(vk: &VerifierKey<E>, comms: &[Commitment<E>], challenge: &Challenge, response: &Response) -> Result<bool> {
+    let num_rows = challenge.rows.len();
+    if response.values.len() != num_rows || response.proofs.len() != num_rows {
+        return Ok(false);
+    }
+    for ((row, value), proof) in challenge.rows.iter().zip(&response.values).zip(&response.proofs) {
+        let comm = comms.get(*row).ok_or(anyhow!("row index {} out of bounds", row))?;
+        if !C::verify(vk, comm, challenge.point, *value, proof)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// verify a non-interactive storage proof: the challenge is re-derived from the commitments root
+/// and the public `randomness` and `nonce`, so a third party only needs the commitments and the verifier key
+pub fn verify_storage_proof(
+    vk: &VerifierKey<E>,
+    comms: &[Commitment<E>],
+    randomness: &[u8],
+    nonce: u64,
+    num_rows: usize,
+    response: &Response,
+) -> Result<bool> {
+    let challenge = Challenge::derive(&commitments_root(comms)?, randomness, nonce, comms.len(), num_rows)?;
+    verify_response(vk, comms, &challenge, response)
+}
+
+/// response to a challenge: the evaluations of the sampled rows at the point with their proofs
+#[derive(Clone, Debug)]
+pub struct Response {
+    pub values: Vec<F>,
+    pub proofs: Vec<Proof<E>>,
+}
+
+/// messages exchanged between the client (data owner) and the storage provider
+#[derive(Clone, Debug)]
+pub enum Message {
+    /// client -> SP: the encoded data matrix and its row commitments
+    Upload { matrix: Matrix<F>, comms: Vec<Commitment<E>> },
+    /// client -> SP: sampling challenge
+    Challenge(Challenge),
+    /// client -> SP: prove storage for the challenge derived from public `randomness` and `nonce`
+    ProveStorage { randomness: Vec<u8>, nonce: u64, num_rows: usize },
+    /// SP -> client: answer to a challenge
+    Response(Response),
+    /// client -> SP: replace the `k` data cells of column `col_idx`
+    Update { col_idx: usize, new_col: Vec<F> },
+    /// SP -> client: the row commitments after an update
+    Updated { comms: Vec<Commitment<E>> },
+    /// client -> SP: point and aggregation challenge for proving the last update
+    UpdateChallenge { point: F, challenge: F },
+    /// SP -> client: proof of the last update
+    UpdateProof(UpdateProof<F, P>),
+    /// SP -> client: the upload was accepted
+    Ack,
+    /// SP -> client: the request failed
+    Error(String),
+}
+
+/// one side of an in-process channel
+pub struct Endpoint {
+    tx: Sender<Message>,
+    rx: Receiver<Message>,
+}
+
+impl Endpoint {
+    pub fn send(&self, msg: Message) -> Result<()> {
+        self.tx.send(msg).map_err(|_| anyhow!("channel closed"))
+    }
+
+    pub fn recv(&self) -> Result<Message> {
+        self.rx.recv().map_err(|_| anyhow!("channel closed"))
+    }
+}
+
+/// a connected pair of endpoints, one for the client and one for the SP
+pub fn channel() -> (Endpoint, Endpoint) {
+    let (client_tx, sp_rx) = mpsc::channel();
+    let (sp_tx, client_rx) = mpsc::channel();
+    (
+        Endpoint { tx: client_tx, rx: client_rx },
+        Endpoint { tx: sp_tx, rx: sp_rx },
+    )
+}
+
+// ------------- Client ------------
+
+/// The client (data owner) only retains the verifier key, the data params and the row commitments
+pub struct Client {
+    endpoint: Endpoint,
+    vk: Option<VerifierKey<E>>,
+    params: Option<Params>,
+    comms: Vec<Commitment<E>>,
+}
+
+impl Client {
+    pub fn new(endpoint: Endpoint) -> Self {
+        Self{
+            endpoint,
+            vk: None,
+            params: None,
+            comms: vec![],
+        }
+    }
+
+    pub fn comms(&self) -> &[Commitment<E>] {
+        &self.comms
+    }
+
+    /// encode and commit to the data matrix, then upload both to the SP
+    pub fn upload(&mut self, srs: &KZG10SRS, mut matrix: Matrix<F>) -> Result<()> {
+        BLSFieldEncoder::encode(&mut matrix)?;
+        let comm = C::commit(srs, &matrix)?;
+        let comms: Vec<Commitment<E>> = comm.comm_output.iter().map(|c| *c.get_comm()).collect();
+
+        self.vk = Some(get_vk(srs)?);
+        self.params = Some(matrix.params.clone());
+        self.comms = comms.clone();
+
+        self.endpoint.send(Message::Upload { matrix, comms })?;
+        match self.endpoint.recv()? {
+            Message::Ack => Ok(()),
+            Message::Error(e) => Err(anyhow!("upload rejected: {}", e)),
+            msg => Err(anyhow!("unexpected message: {:?}", msg)),
+        }
+    }
+
+    /// challenge the SP on `num_rows` rows derived from `seed` and verify its response
+    pub fn challenge(&self, seed: u64, num_rows: usize) -> Result<bool> {
+        let (vk, params) = self.state()?;
+        let challenge = Challenge::from_seed(seed, params.n, num_rows)?;
+
+        self.endpoint.send(Message::Challenge(challenge.clone()))?;
+        let response = match self.endpoint.recv()? {
+            Message::Response(response) => response,
+            Message::Error(e) => return Err(anyhow!("challenge failed: {}", e)),
+            msg => return Err(anyhow!("unexpected message: {:?}", msg)),
+        };
+
+        verify_response(vk, &self.comms, &challenge, &response)
+    }
+
+    /// ask the SP for a non-interactive storage proof bound to the public `randomness` and `nonce`,
+    /// returns the response so it can be handed to a third party along with the commitments
+    pub fn prove_storage(&self, randomness: &[u8], nonce: u64, num_rows: usize) -> Result<(bool, Response)> {
+        let (vk, _) = self.state()?;
+        self.endpoint.send(Message::ProveStorage { randomness: randomness.to_vec(), nonce, num_rows })?;
+        let response = match self.endpoint.recv()? {
+            Message::Response(response) => response,
+            Message::Error(e) => return Err(anyhow!("storage proof failed: {}", e)),
+            msg => return Err(anyhow!("unexpected message: {:?}", msg)),
+        };
+        let valid = verify_storage_proof(vk, &self.comms, randomness, nonce, num_rows, &response)?;
+        Ok((valid, response))
+    }
+
+    /// replace the data cells of column `col_idx`, the SP's updated commitments are only adopted
+    /// once it proves they changed by a valid column delta, at a point derived from `seed`
+    pub fn update(&mut self, col_idx: usize, new_col: Vec<F>, seed: u64) -> Result<bool> {
+        let (vk, params) = self.state()?;
+        if new_col.len() != params.k {
+            return Err(anyhow!("new_col length ({}) must equal k ({})", new_col.len(), params.k));
+        }
+
+        self.endpoint.send(Message::Update { col_idx, new_col })?;
+        let new_comms = match self.endpoint.recv()? {
+            Message::Updated { comms } => comms,
+            Message::Error(e) => return Err(anyhow!("update failed: {}", e)),
+            msg => return Err(anyhow!("unexpected message: {:?}", msg)),
+        };
+
+        // challenge the SP only after it fixed the new commitments
+        let mut rng = StdRng::seed_from_u64(seed);
+        let point = F::rand(&mut rng);
+        let challenge = F::rand(&mut rng);
+        self.endpoint.send(Message::UpdateChallenge { point, challenge })?;
+        let proof = match self.endpoint.recv()? {
+            Message::UpdateProof(proof) => proof,
+            Message::Error(e) => return Err(anyhow!("update proof failed: {}", e)),
+            msg => return Err(anyhow!("unexpected message: {:?}", msg)),
+        };
+
+        if proof.col_idx != col_idx || new_comms.len() != self.comms.len() {
+            return Ok(false);
+        }
+        let old_refs: Vec<&Commitment<E>> = self.comms.iter().collect();
+        let new_refs: Vec<&Commitment<E>> = new_comms.iter().collect();
+        if !C::verify_update::<BLSFieldEncoder<F>>(vk, params, &old_refs, &new_refs, &proof, point, challenge)? {
+            return Ok(false);
+        }
+        self.comms = new_comms;
+        Ok(true)
+    }
+
+    fn state(&self) -> Result<(&VerifierKey<E>, &Params)> {
+        match (&self.vk, &self.params) {
+            (Some(vk), Some(params)) => Ok((vk, params)),
+            _ => Err(anyhow!("no data uploaded yet")),
+        }
+    }
+}
+
+// ------------- Storage Provider ------------
+
+/// The storage provider keeps the encoded data matrix and the row polynomials
+pub struct StorageProvider {
+    endpoint: Endpoint,
+    srs: KZG10SRS,
+    matrix: Option<Matrix<F>>,
+    comm: Option<MatrixCommitOutput<F, P>>,
+    /// state kept between an update and its proof
+    pending_update: Option<PendingUpdate>,
+}
+
+struct PendingUpdate {
+    old_comm: MatrixCommitOutput<F, P>,
+    update: ColumnUpdate<F>,
+}
+
+impl StorageProvider {
+    pub fn new(endpoint: Endpoint, srs: KZG10SRS) -> Self {
+        Self{
+            endpoint,
+            srs,
+            matrix: None,
+            comm: None,
+            pending_update: None,
+        }
+    }
+
+    /// handle requests until the client hangs up
+    pub fn serve(mut self) -> Result<()> {
+        while let Ok(msg) = self.endpoint.rx.recv() {
+            let reply = match self.handle(msg) {
+                Ok(reply) => reply,
+                Err(e) => Message::Error(e.to_string()),
+            };
+            self.endpoint.send(reply)?;
+        }
+        Ok(())
+    }
+
+    /// handle a single request and return the reply
+    pub fn handle(&mut self, msg: Message) -> Result<Message> {
+        match msg {
+            Message::Upload { matrix, comms } => {
+                // recompute the row polynomials and check they match the client's commitments
+                let comm = C::commit(&self.srs, &matrix)?;
+                let matches = comm.comm_output.len() == comms.len()
+                    && comm.comm_output.iter().zip(&comms).all(|(c, d)| c.get_comm() == d);
+                if !matches {
+                    return Err(anyhow!("commitments don't match the uploaded matrix"));
+                }
+                self.matrix = Some(matrix);
+                self.comm = Some(comm);
+                Ok(Message::Ack)
+            }
+            Message::Challenge(challenge) => Ok(Message::Response(self.respond(&challenge)?)),
+            Message::ProveStorage { randomness, nonce, num_rows } => {
+                let comm = self.comm.as_ref().ok_or(anyhow!("no data stored"))?;
+                let comms: Vec<Commitment<E>> = comm.comm_output.iter().map(|c| *c.get_comm()).collect();
+                let challenge = Challenge::derive(&commitments_root(&comms)?, &randomness, nonce, comms.len(), num_rows)?;
+                Ok(Message::Response(self.respond(&challenge)?))
+            }
+            Message::Update { col_idx, new_col } => {
+                let (matrix, comm) = match (self.matrix.as_mut(), self.comm.as_mut()) {
+                    (Some(matrix), Some(comm)) => (matrix, comm),
+                    _ => return Err(anyhow!("no data stored")),
+                };
+                matrix.params.check_cols(col_idx)?;
+                if new_col.len() != matrix.params.k {
+                    return Err(anyhow!("new_col length ({}) must equal k ({})", new_col.len(), matrix.params.k));
+                }
+                let old_comm = comm.clone();
+                let old_col = matrix.get_col(col_idx)?;
+                matrix.update_col(col_idx, &new_col)?;
+                let (changed_rows, old_vals) = changed_cells(&old_col, &new_col);
+                BLSFieldEncoder::update_parity(matrix, col_idx, &changed_rows, &old_vals)?;
+                let encoded_new_col = matrix.get_col(col_idx)?;
+                C::update_commitments(&self.srs, comm, col_idx, &old_col, &encoded_new_col)?;
+
+                let comms = comm.comm_output.iter().map(|c| *c.get_comm()).collect();
+                self.pending_update = Some(PendingUpdate {
+                    old_comm,
+                    update: ColumnUpdate::new(col_idx, old_col, encoded_new_col),
+                });
+                Ok(Message::Updated { comms })
+            }
+            Message::UpdateChallenge { point, challenge } => {
+                let comm = self.comm.as_ref().ok_or(anyhow!("no data stored"))?;
+                let pending = self.pending_update.take().ok_or(anyhow!("no pending update"))?;
+                let proof = C::prove_update(
+                    &self.srs,
+                    &pending.old_comm,
+                    comm,
+                    &pending.update,
+                    point,
+                    challenge,
+                )?;
+                Ok(Message::UpdateProof(proof))
+            }
+            msg => Err(anyhow!("unexpected message: {:?}", msg)),
+        }
+    }
+
+    /// evaluate the challenged rows at the point and open them
+    fn respond(&self, challenge: &Challenge) -> Result<Response> {
+        let comm = self.comm.as_ref().ok_or(anyhow!("no data stored"))?;
+        if let Some(row) = challenge.rows.iter().find(|&&row| row >= comm.comm_output.len()) {
+            return Err(anyhow!("row index {} out of bounds", row));
+        }
+        // the rows are opened in parallel with the `parallel` feature
+        let srs = &self.srs;
+        let (values, proofs) = cfg_iter!(challenge.rows)
+            .map(|&row| Ok((comm.get_poly(row).evaluate(&challenge.point), C::open(comm, srs, row, challenge.point)?)))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+        Ok(Response { values, proofs })
+    }
+}
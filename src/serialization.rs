@@ -0,0 +1,408 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use ark_bls12_381::Fr;
+use ark_ec::pairing::Pairing;
+use ark_ff::Field;
+use ark_poly_commit::kzg10::{Commitment, Proof, UniversalParams, VerifierKey};
+use ark_poly_commit::LabeledPolynomial;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate};
+use crate::byte_data::{Data, Manifest, Params};
+use crate::field_matrix::Matrix;
+use crate::kzg10::{KZG10CommitOutput, UniPoly, F};
+use crate::protocol::Challenge;
+use crate::traits::{MatrixCommitOutput, PolyCommScheme};
+
+// ------------- Container ------------
+
+/// every container starts with these bytes
+pub const MAGIC: [u8; 4] = *b"DDEX";
+/// version of the container format, bumped on incompatible changes
+pub const VERSION: u16 = 1;
+
+/// what is stored in a container, checked on load so that e.g. a proof can't be read as a commitment
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Params = 0,
+    Manifest = 1,
+    ByteMatrix = 2,
+    FieldMatrix = 3,
+    CommitOutput = 4,
+    MatrixCommitOutput = 5,
+    Commitments = 6,
+    Proof = 7,
+    Proofs = 8,
+    SRS = 9,
+    VerifierKey = 10,
+    Challenge = 11,
+    Evaluations = 12,
+}
+
+impl Kind {
+    fn from_tag(tag: u8) -> Result<Self> {
+        let kind = match tag {
+            0 => Kind::Params,
+            1 => Kind::Manifest,
+            2 => Kind::ByteMatrix,
+            3 => Kind::FieldMatrix,
+            4 => Kind::CommitOutput,
+            5 => Kind::MatrixCommitOutput,
+            6 => Kind::Commitments,
+            7 => Kind::Proof,
+            8 => Kind::Proofs,
+            9 => Kind::SRS,
+            10 => Kind::VerifierKey,
+            11 => Kind::Challenge,
+            12 => Kind::Evaluations,
+            _ => return Err(anyhow!("unknown container kind {}", tag)),
+        };
+        Ok(kind)
+    }
+}
+
+/// types that can be stored in a versioned container
+pub trait Persist: CanonicalSerialize + CanonicalDeserialize {
+    const KIND: Kind;
+}
+
+/// container layout: `MAGIC | VERSION (u16 LE) | kind (u8) | compressed (u8) | payload`
+pub fn to_writer<T: Persist, W: Write>(value: &T, compress: Compress, mut writer: W) -> Result<()> {
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    writer.write_all(&[T::KIND as u8, (compress == Compress::Yes) as u8])?;
+    value.serialize_with_mode(&mut writer, compress)?;
+    Ok(())
+}
+
+/// read a container, the payload is always validated (subgroup checks, matrix shapes, ...)
+pub fn from_reader<T: Persist, R: Read>(mut reader: R) -> Result<T> {
+    let mut header = [0u8; 8];
+    reader.read_exact(&mut header)?;
+    if header[0..4] != MAGIC {
+        return Err(anyhow!("not a container, bad magic bytes"));
+    }
+    let version = u16::from_le_bytes([header[4], header[5]]);
+    if version != VERSION {
+        return Err(anyhow!("unsupported container version {}, expected {}", version, VERSION));
+    }
+    let kind = Kind::from_tag(header[6])?;
+    if kind != T::KIND {
+        return Err(anyhow!("container holds {:?}, expected {:?}", kind, T::KIND));
+    }
+    let compress = match header[7] {
+        0 => Compress::No,
+        1 => Compress::Yes,
+        flag => return Err(anyhow!("invalid compression flag {}", flag)),
+    };
+    Ok(T::deserialize_with_mode(&mut reader, compress, Validate::Yes)?)
+}
+
+/// the kind of the container in `bytes`, without decoding the payload
+pub fn peek_kind(bytes: &[u8]) -> Result<Kind> {
+    if bytes.len() < 8 || bytes[0..4] != MAGIC {
+        return Err(anyhow!("not a container, bad magic bytes"));
+    }
+    Kind::from_tag(bytes[6])
+}
+
+pub fn to_bytes<T: Persist>(value: &T, compress: Compress) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+    to_writer(value, compress, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// like [`from_reader`] but also rejects trailing bytes
+pub fn from_bytes<T: Persist>(bytes: &[u8]) -> Result<T> {
+    let mut reader = bytes;
+    let value = from_reader(&mut reader)?;
+    if !reader.is_empty() {
+        return Err(anyhow!("{} trailing bytes after the container", reader.len()));
+    }
+    Ok(value)
+}
+
+pub fn write_file<T: Persist>(path: &Path, value: &T, compress: Compress) -> Result<()> {
+    fs::write(path, to_bytes(value, compress)?)?;
+    Ok(())
+}
+
+pub fn read_file<T: Persist>(path: &Path) -> Result<T> {
+    from_bytes(&fs::read(path)?)
+}
+
+impl Persist for Params { const KIND: Kind = Kind::Params; }
+impl Persist for Manifest { const KIND: Kind = Kind::Manifest; }
+impl Persist for Data<u8> { const KIND: Kind = Kind::ByteMatrix; }
+impl<T: Field> Persist for Matrix<T> { const KIND: Kind = Kind::FieldMatrix; }
+impl<E: Pairing> Persist for KZG10CommitOutput<E> { const KIND: Kind = Kind::CommitOutput; }
+impl<T: Field, P: PolyCommScheme<T>> Persist for MatrixCommitOutput<T, P>
+where P::CommitOutput: CanonicalSerialize + CanonicalDeserialize {
+    const KIND: Kind = Kind::MatrixCommitOutput;
+}
+impl<E: Pairing> Persist for Vec<Commitment<E>> { const KIND: Kind = Kind::Commitments; }
+impl<E: Pairing> Persist for Proof<E> { const KIND: Kind = Kind::Proof; }
+impl<E: Pairing> Persist for Vec<Proof<E>> { const KIND: Kind = Kind::Proofs; }
+impl<E: Pairing> Persist for UniversalParams<E> { const KIND: Kind = Kind::SRS; }
+impl<E: Pairing> Persist for VerifierKey<E> { const KIND: Kind = Kind::VerifierKey; }
+impl Persist for Challenge { const KIND: Kind = Kind::Challenge; }
+/// the evaluations of the sampled rows in a response, `Fr` is `F` spelled out so it can't overlap the commitments
+impl Persist for Vec<Fr> { const KIND: Kind = Kind::Evaluations; }
+
+// ------------- Params ------------
+
+impl Valid for Params {
+    fn check(&self) -> Result<(), SerializationError> {
+        if self.k == 0 || self.k > self.n {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(())
+    }
+}
+
+impl CanonicalSerialize for Params {
+    fn serialize_with_mode<W: Write>(&self, mut writer: W, compress: Compress) -> Result<(), SerializationError> {
+        self.k.serialize_with_mode(&mut writer, compress)?;
+        self.n.serialize_with_mode(&mut writer, compress)?;
+        self.m.serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        3 * 0usize.serialized_size(compress)
+    }
+}
+
+impl CanonicalDeserialize for Params {
+    fn deserialize_with_mode<R: Read>(mut reader: R, compress: Compress, validate: Validate) -> Result<Self, SerializationError> {
+        let params = Params{
+            k: usize::deserialize_with_mode(&mut reader, compress, validate)?,
+            n: usize::deserialize_with_mode(&mut reader, compress, validate)?,
+            m: usize::deserialize_with_mode(&mut reader, compress, validate)?,
+        };
+        if validate == Validate::Yes {
+            params.check()?;
+        }
+        Ok(params)
+    }
+}
+
+// ------------- Manifest ------------
+
+impl Valid for Manifest {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.params.check()?;
+        if self.original_len > self.params.capacity() {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(())
+    }
+}
+
+impl CanonicalSerialize for Manifest {
+    fn serialize_with_mode<W: Write>(&self, mut writer: W, compress: Compress) -> Result<(), SerializationError> {
+        self.params.serialize_with_mode(&mut writer, compress)?;
+        self.original_len.serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.params.serialized_size(compress) + self.original_len.serialized_size(compress)
+    }
+}
+
+impl CanonicalDeserialize for Manifest {
+    fn deserialize_with_mode<R: Read>(mut reader: R, compress: Compress, validate: Validate) -> Result<Self, SerializationError> {
+        let manifest = Manifest{
+            params: Params::deserialize_with_mode(&mut reader, compress, validate)?,
+            original_len: usize::deserialize_with_mode(&mut reader, compress, validate)?,
+        };
+        if validate == Validate::Yes {
+            manifest.check()?;
+        }
+        Ok(manifest)
+    }
+}
+
+// ------------- Matrices ------------
+
+/// the rows must match the `n`x`m` shape in the params
+fn check_shape<T>(params: &Params, rows: &[Vec<T>]) -> Result<(), SerializationError> {
+    params.check()?;
+    if rows.len() != params.n || rows.iter().any(|row| row.len() != params.m) {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(())
+}
+
+impl Valid for Data<u8> {
+    fn check(&self) -> Result<(), SerializationError> {
+        check_shape(&self.params, &self.matrix)
+    }
+}
+
+impl CanonicalSerialize for Data<u8> {
+    fn serialize_with_mode<W: Write>(&self, mut writer: W, compress: Compress) -> Result<(), SerializationError> {
+        self.params.serialize_with_mode(&mut writer, compress)?;
+        self.matrix.serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.params.serialized_size(compress) + self.matrix.serialized_size(compress)
+    }
+}
+
+impl CanonicalDeserialize for Data<u8> {
+    fn deserialize_with_mode<R: Read>(mut reader: R, compress: Compress, validate: Validate) -> Result<Self, SerializationError> {
+        let data = Data{
+            params: Params::deserialize_with_mode(&mut reader, compress, validate)?,
+            matrix: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+        };
+        if validate == Validate::Yes {
+            data.check()?;
+        }
+        Ok(data)
+    }
+}
+
+impl<T: Field> Valid for Matrix<T> {
+    fn check(&self) -> Result<(), SerializationError> {
+        check_shape(&self.params, &self.elms)
+    }
+}
+
+impl<T: Field> CanonicalSerialize for Matrix<T> {
+    fn serialize_with_mode<W: Write>(&self, mut writer: W, compress: Compress) -> Result<(), SerializationError> {
+        self.params.serialize_with_mode(&mut writer, compress)?;
+        self.elms.serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.params.serialized_size(compress) + self.elms.serialized_size(compress)
+    }
+}
+
+impl<T: Field> CanonicalDeserialize for Matrix<T> {
+    fn deserialize_with_mode<R: Read>(mut reader: R, compress: Compress, validate: Validate) -> Result<Self, SerializationError> {
+        let matrix = Matrix{
+            params: Params::deserialize_with_mode(&mut reader, compress, validate)?,
+            elms: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+        };
+        if validate == Validate::Yes {
+            matrix.check()?;
+        }
+        Ok(matrix)
+    }
+}
+
+// ------------- Commitments ------------
+
+impl<E: Pairing> Valid for KZG10CommitOutput<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.comm.check()?;
+        if let Some(bound) = self.poly.degree_bound()
+            && self.poly.degree() > bound {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(())
+    }
+}
+
+impl<E: Pairing> CanonicalSerialize for KZG10CommitOutput<E> {
+    fn serialize_with_mode<W: Write>(&self, mut writer: W, compress: Compress) -> Result<(), SerializationError> {
+        self.poly.label().serialize_with_mode(&mut writer, compress)?;
+        self.poly.polynomial().serialize_with_mode(&mut writer, compress)?;
+        self.poly.degree_bound().serialize_with_mode(&mut writer, compress)?;
+        self.poly.hiding_bound().serialize_with_mode(&mut writer, compress)?;
+        self.comm.serialize_with_mode(&mut writer, compress)?;
+        self.rand.serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.poly.label().serialized_size(compress)
+            + self.poly.polynomial().serialized_size(compress)
+            + self.poly.degree_bound().serialized_size(compress)
+            + self.poly.hiding_bound().serialized_size(compress)
+            + self.comm.serialized_size(compress)
+            + self.rand.serialized_size(compress)
+    }
+}
+
+impl<E: Pairing> CanonicalDeserialize for KZG10CommitOutput<E> {
+    fn deserialize_with_mode<R: Read>(mut reader: R, compress: Compress, validate: Validate) -> Result<Self, SerializationError> {
+        let label = String::deserialize_with_mode(&mut reader, compress, validate)?;
+        let polynomial = UniPoly::<E>::deserialize_with_mode(&mut reader, compress, validate)?;
+        let degree_bound = Option::<usize>::deserialize_with_mode(&mut reader, compress, validate)?;
+        let hiding_bound = Option::<usize>::deserialize_with_mode(&mut reader, compress, validate)?;
+        let output = KZG10CommitOutput::new(
+            LabeledPolynomial::new(label, polynomial, degree_bound, hiding_bound),
+            Commitment::<E>::deserialize_with_mode(&mut reader, compress, validate)?,
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?,
+        );
+        if validate == Validate::Yes {
+            output.check()?;
+        }
+        Ok(output)
+    }
+}
+
+impl<T: Field, P: PolyCommScheme<T>> Valid for MatrixCommitOutput<T, P>
+where P::CommitOutput: CanonicalSerialize + CanonicalDeserialize {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.comm_output.check()
+    }
+}
+
+impl<T: Field, P: PolyCommScheme<T>> CanonicalSerialize for MatrixCommitOutput<T, P>
+where P::CommitOutput: CanonicalSerialize + CanonicalDeserialize {
+    fn serialize_with_mode<W: Write>(&self, writer: W, compress: Compress) -> Result<(), SerializationError> {
+        self.comm_output.serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.comm_output.serialized_size(compress)
+    }
+}
+
+impl<T: Field, P: PolyCommScheme<T>> CanonicalDeserialize for MatrixCommitOutput<T, P>
+where P::CommitOutput: CanonicalSerialize + CanonicalDeserialize {
+    fn deserialize_with_mode<R: Read>(reader: R, compress: Compress, validate: Validate) -> Result<Self, SerializationError> {
+        Ok(MatrixCommitOutput::new(
+            Vec::deserialize_with_mode(reader, compress, validate)?
+        ))
+    }
+}
+
+// ------------- Challenge ------------
+
+impl Valid for Challenge {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.point.check()
+    }
+}
+
+impl CanonicalSerialize for Challenge {
+    fn serialize_with_mode<W: Write>(&self, mut writer: W, compress: Compress) -> Result<(), SerializationError> {
+        self.seed.serialize_with_mode(&mut writer, compress)?;
+        self.rows.serialize_with_mode(&mut writer, compress)?;
+        self.point.serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.seed.serialized_size(compress)
+            + self.rows.serialized_size(compress)
+            + self.point.serialized_size(compress)
+    }
+}
+
+impl CanonicalDeserialize for Challenge {
+    fn deserialize_with_mode<R: Read>(mut reader: R, compress: Compress, validate: Validate) -> Result<Self, SerializationError> {
+        let challenge = Challenge{
+            seed: u64::deserialize_with_mode(&mut reader, compress, validate)?,
+            rows: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            point: F::deserialize_with_mode(&mut reader, compress, validate)?,
+        };
+        if validate == Validate::Yes {
+            challenge.check()?;
+        }
+        Ok(challenge)
+    }
+}
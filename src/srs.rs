@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, Result};
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, VariableBaseMSM};
+use ark_poly_commit::kzg10::UniversalParams;
+use ark_serialize::CanonicalDeserialize;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use ark_std::UniformRand;
+use crate::kzg10::KZG10SRS;
+use crate::serialization::{self, MAGIC};
+
+/// where the SRS (powers of tau) for the KZG setup comes from
+#[derive(Clone, Debug)]
+pub enum SetupSource {
+    /// powers of tau from a trusted setup ceremony file
+    File { path: PathBuf, format: SrsFormat },
+    /// random setup from `test_rng`, the toxic waste is known to anyone so this is only for tests and benchmarks
+    InsecureTest,
+}
+
+/// supported ceremony file formats
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SrsFormat {
+    /// the Ethereum KZG ceremony `trusted_setup.txt` as shipped with c-kzg-4844:
+    /// the number of G1 and G2 points, the G1 points in Lagrange form, the G2 points
+    /// and then the G1 points in monomial form, one hex encoded compressed point per line
+    EthereumCeremony,
+    /// `UniversalParams` serialized (compressed) with arkworks, either raw or in a container
+    Arkworks,
+}
+
+/// load the SRS for polynomials of degree up to `degree` from the file,
+/// the powers are truncated to the degree and checked for pairing consistency
+pub fn load_srs<E: Pairing>(path: &Path, format: SrsFormat, degree: usize) -> Result<KZG10SRS<E>> {
+    let srs = match format {
+        SrsFormat::EthereumCeremony => parse_ethereum_ceremony::<E>(&fs::read_to_string(path)?, degree)?,
+        SrsFormat::Arkworks => {
+            let bytes = fs::read(path)?;
+            let srs: KZG10SRS<E> = if bytes.starts_with(&MAGIC) {
+                serialization::from_bytes(&bytes)?
+            } else {
+                UniversalParams::deserialize_compressed(bytes.as_slice())?
+            };
+            truncate(srs, degree)?
+        }
+    };
+    check_powers(&srs)?;
+    Ok(srs)
+}
+
+/// keep the powers needed for polynomials of degree up to `degree`
+pub fn truncate<E: Pairing>(mut srs: KZG10SRS<E>, degree: usize) -> Result<KZG10SRS<E>> {
+    if srs.powers_of_g.len() <= degree {
+        return Err(anyhow!(
+            "the SRS supports degree {} but degree {} is needed",
+            srs.powers_of_g.len().saturating_sub(1),
+            degree
+        ));
+    }
+    srs.powers_of_g.truncate(degree + 1);
+    srs.powers_of_gamma_g.retain(|i, _| *i <= degree);
+    Ok(srs)
+}
+
+/// check the SRS is made of consecutive powers, i.e. `e(g_{i+1}, h) = e(g_i, beta_h)` for all `i`
+/// (and the same for the powers of `gamma_g` if present).
+/// the checks are batched with a random linear combination, so it costs two MSMs and two pairings
+pub fn check_powers<E: Pairing>(srs: &KZG10SRS<E>) -> Result<()> {
+    if srs.powers_of_g.is_empty() || srs.powers_of_g[0].is_zero() || srs.h.is_zero() || srs.beta_h.is_zero() {
+        return Err(anyhow!("the SRS contains the point at infinity"));
+    }
+    let gamma_powers: Vec<E::G1Affine> = srs.powers_of_gamma_g.values().copied().collect();
+    if !srs.powers_of_gamma_g.keys().copied().eq(0..gamma_powers.len()) {
+        return Err(anyhow!("the powers of gamma_g are not consecutive"));
+    }
+    if !consecutive_powers::<E>(&srs.powers_of_g, srs.h, srs.beta_h)
+        || !consecutive_powers::<E>(&gamma_powers, srs.h, srs.beta_h) {
+        return Err(anyhow!("the SRS powers are not consistent"));
+    }
+    Ok(())
+}
+
+fn consecutive_powers<E: Pairing>(powers: &[E::G1Affine], h: E::G2Affine, beta_h: E::G2Affine) -> bool {
+    if powers.len() < 2 {
+        return true;
+    }
+    let rng = &mut StdRng::from_seed(rand::random());
+    let scalars: Vec<E::ScalarField> = (0..powers.len() - 1).map(|_| E::ScalarField::rand(rng)).collect();
+    let lhs = E::G1::msm(&powers[1..], &scalars).unwrap();
+    let rhs = E::G1::msm(&powers[..powers.len() - 1], &scalars).unwrap();
+    E::pairing(lhs, h) == E::pairing(rhs, beta_h)
+}
+
+/// the ceremony is over BLS12-381, points of other curves have a different size and are rejected
+fn parse_ethereum_ceremony<E: Pairing>(text: &str, degree: usize) -> Result<KZG10SRS<E>> {
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    let mut next_line = || lines.next().ok_or(anyhow!("unexpected end of the ceremony file"));
+    let num_g1: usize = next_line()?.parse()?;
+    let num_g2: usize = next_line()?.parse()?;
+    if num_g1 <= degree {
+        return Err(anyhow!("the ceremony supports degree {} but degree {} is needed", num_g1.saturating_sub(1), degree));
+    }
+    if num_g2 < 2 {
+        return Err(anyhow!("the ceremony needs at least 2 G2 points, found {}", num_g2));
+    }
+
+    // G1 points in Lagrange form are not used
+    for _ in 0..num_g1 {
+        next_line()?;
+    }
+    let mut g2 = Vec::with_capacity(2);
+    for i in 0..num_g2 {
+        let line = next_line()?;
+        if i < 2 {
+            g2.push(decode_point::<E::G2Affine>(line)?);
+        }
+    }
+    let mut powers_of_g = Vec::with_capacity(degree + 1);
+    for _ in 0..=degree {
+        let line = next_line().map_err(|_| anyhow!("the ceremony file has no G1 points in monomial form"))?;
+        powers_of_g.push(decode_point::<E::G1Affine>(line)?);
+    }
+
+    let (h, beta_h) = (g2[0], g2[1]);
+    Ok(UniversalParams {
+        powers_of_g,
+        // the ceremony has no powers of a second generator, hence no hiding commitments
+        powers_of_gamma_g: BTreeMap::new(),
+        h,
+        beta_h,
+        neg_powers_of_h: BTreeMap::new(),
+        prepared_h: h.into(),
+        prepared_beta_h: beta_h.into(),
+    })
+}
+
+/// decode a hex encoded compressed point, checking its size matches the curve
+fn decode_point<G: AffineRepr>(line: &str) -> Result<G> {
+    let bytes = decode_hex(line)?;
+    let size = G::generator().compressed_size();
+    if bytes.len() != size {
+        return Err(anyhow!("ceremony point has {} bytes but the curve uses {}", bytes.len(), size));
+    }
+    Ok(G::deserialize_compressed(bytes.as_slice())?)
+}
+
+/// decode a hex string with an optional `0x` prefix, fails on anything but pairs of hex digits
+pub fn decode_hex(s: &str) -> Result<Vec<u8>> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    if !s.len().is_multiple_of(2) {
+        return Err(anyhow!("odd length hex string"));
+    }
+    let digit = |b: u8| (b as char).to_digit(16).ok_or(anyhow!("invalid hex digit {:#04x}", b));
+    s.as_bytes()
+        .chunks(2)
+        .map(|pair| Ok((digit(pair[0])? * 16 + digit(pair[1])?) as u8))
+        .collect()
+}
+
@@ -1,13 +1,28 @@
 #[cfg(test)]
 mod tests {
-    use crate::byte_data::{Data, Params};
-    use crate::kzg10::{E, F, get_vk, KZG10PolyComm};
-    use crate::field_matrix::Matrix;
+    use crate::byte_data::{Data, Manifest, Params};
+    use ark_ec::pairing::Pairing;
+    use crate::kzg10::{E, F, Fr, get_vk, KZG10CommitOutput, KZG10PolyComm, KZG10SRS};
+    use crate::kzg10_lagrange::KZG10LagrangePolyComm;
+    use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+    use ark_serialize::{CanonicalSerialize, Compress};
+    use ark_ff::{BigInteger, PrimeField, Zero};
+    use crate::fat_matrix::{FatMatrix, FatParams};
+    use crate::field_matrix::{packed_bytes_per_elm, Matrix};
     use ark_poly_commit::kzg10::Commitment;
+    use ark_poly::{EvaluationDomain, Polynomial};
     use ark_std::{test_rng, UniformRand};
-    use crate::encoder::{BLSEncoder, BLSFieldEncoder, G8Encoder};
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use crate::encoder::{changed_cells, BLSData, BLSEncoder, BLSFatEncoder, BLSFieldEncoder, ColumnDomain, G8Encoder, FIELD_CELL_BYTES};
     use crate::matrix_commit::MatrixPolyComm;
-    use crate::traits::{DataMatrix, Encoder, PolyCommScheme, MatrixPolyCommScheme, CommitOutputTrait};
+    use crate::matrix_2d::Matrix2DPolyComm;
+    use crate::serialization::{from_bytes, peek_kind, to_bytes, Kind};
+    use crate::srs::{decode_hex, load_srs, SetupSource, SrsFormat};
+    use crate::merkle::{MerkleHasher, MerkleProof, MerkleTree, PoseidonHasher, Sha256Hasher};
+    use crate::protocol::{channel, commitments_root, verify_response, verify_storage_proof, Challenge, Client, StorageProvider};
+    use crate::transcript::Transcript;
+    use crate::versioning::VersionedStore;
+    use crate::traits::{DataMatrix, Encoder, PolyCommScheme, MatrixPolyCommScheme, CommitOutputTrait, ColumnUpdate, MatrixCommitOutput};
 
     #[test]
     fn test_encode_columns() {
@@ -68,35 +83,53 @@ mod tests {
             n,
             m,
         };
-        let mut data = Data::new_random(params);
-        println!("data #row ={}", data.matrix.len());
-        println!("data #col ={}", data.matrix[0].len());
+        let mut data = BLSData::new(Data::new_random(params));
+        println!("data #row ={}", data.data.matrix.len());
+        println!("data #col ={}", data.data.matrix[0].len());
         println!("data before encoding:");
-        data.pretty_print();
+        data.data.pretty_print();
         // original data matrix
-        let original: Vec<Vec<u8>> = data.matrix[..k].to_vec();
+        let original: Vec<Vec<u8>> = data.data.matrix[..k].to_vec();
 
         // encode
         BLSEncoder::encode(&mut data).expect("encode failed");
-        println!("data after encoding:");
-        data.pretty_print();
 
-        // verify data matrix unchanged
-        assert_eq!(data.matrix[..k], original[..]);
+        // verify data matrix unchanged and keeping its shape
+        assert_eq!(data.data.matrix[..k], original[..]);
+        assert!(data.data.matrix.iter().all(|row| row.len() == m));
+
+        // every column is a codeword, with the parity rows stored as serialized field elements
+        let rows = data.to_rows();
+        for row in &rows[k..] {
+            assert_eq!(row.len(), m * FIELD_CELL_BYTES);
+        }
+        for c in 0..m {
+            let col: Vec<u8> = rows[..k].iter().map(|row| row[c])
+                .chain(rows[k..].iter().flat_map(|row| row[c * FIELD_CELL_BYTES..(c + 1) * FIELD_CELL_BYTES].to_vec()))
+                .collect();
+            assert!(BLSEncoder::is_codeword(data.params().clone(), &col).expect("is codeword"));
+        }
 
         // simulate loss of one data and one parity rows
-        let mut matrix_opts: Vec<_> = data.matrix.iter().cloned().map(Some).collect();
+        let mut matrix_opts: Vec<_> = rows.iter().cloned().map(Some).collect();
         matrix_opts[1] = None;
         matrix_opts[k] = None;
 
-        // TODO: reconstruct missing rows
-        // BLSEncoder::reconstruct(data.params.clone(), &mut matrix_opts).expect("reconstruction should succeed");
+        // reconstruct missing rows
+        BLSEncoder::reconstruct(data.params().clone(), &mut matrix_opts).expect("reconstruction should succeed");
+
+        // verify reconstruction for data and parity rows
+        for (recovered, row) in matrix_opts.iter().zip(&rows) {
+            assert_eq!(recovered.as_ref(), Some(row));
+        }
 
-        // verify reconstruction for data shards
-        // for i in 0..k {
-        //     let recovered = matrix_opts[i].clone().unwrap();
-        //     assert_eq!(recovered, &original[i][..]);
-        // }
+        // a parity cell at or above the field modulus is rejected instead of being reduced
+        for bad_cell in [F::MODULUS.to_bytes_le(), vec![0xff; FIELD_CELL_BYTES]] {
+            let mut matrix_opts: Vec<_> = rows.iter().cloned().map(Some).collect();
+            matrix_opts[1] = None;
+            matrix_opts[k + 1].as_mut().unwrap()[..FIELD_CELL_BYTES].copy_from_slice(&bad_cell);
+            assert!(BLSEncoder::reconstruct(data.params().clone(), &mut matrix_opts).is_err());
+        }
     }
 
     #[test]
@@ -133,18 +166,94 @@ mod tests {
         assert_eq!(original.elms[..k], original_copy.elms[..k]);
 
         // simulate loss of one data and one parity rows
-        let mut matrix_opts: Vec<_> = data.matrix.iter().cloned().map(Some).collect();
+        let mut matrix_opts: Vec<_> = original.elms.iter().cloned().map(Some).collect();
         matrix_opts[1] = None;
         matrix_opts[k] = None;
 
-        // TODO: reconstruct missing rows
-        // BLSEncoder::reconstruct(data.params.clone(), &mut matrix_opts).expect("reconstruction should succeed");
+        // reconstruct missing rows
+        BLSFieldEncoder::reconstruct(original.params.clone(), &mut matrix_opts).expect("reconstruction should succeed");
 
-        // verify reconstruction for data shards
-        // for i in 0..k {
-        //     let recovered = matrix_opts[i].clone().unwrap();
-        //     assert_eq!(recovered, &original[i][..]);
-        // }
+        // verify reconstruction for data and parity rows
+        for (recovered, row) in matrix_opts.iter().zip(&original.elms) {
+            assert_eq!(recovered.as_ref(), Some(row));
+        }
+
+        // losing more than p rows must fail
+        let mut matrix_opts: Vec<_> = original.elms.iter().cloned().map(Some).collect();
+        for row in matrix_opts.iter_mut().take(p + 1) {
+            *row = None;
+        }
+        assert!(BLSFieldEncoder::reconstruct(original.params.clone(), &mut matrix_opts).is_err());
+    }
+
+    #[test]
+    fn test_bls_field_encoder_systematic() {
+        let mut rng = rand::rng();
+        // (k, n) pairs, including sizes that are not powers of two and more parity rows than data rows
+        for (k, n) in [(4, 8), (3, 4), (5, 9), (6, 7), (16, 32), (3, 12), (2, 9)] {
+            let m = 4;
+            let params = Params {
+                k,
+                n,
+                m,
+            };
+            let mut matrix = Matrix::<F>::new_random(params.clone());
+            BLSFieldEncoder::encode(&mut matrix).expect("encode failed");
+
+            // the column polynomial evaluates to the data at the data points and to the parity elsewhere
+            let domain = ColumnDomain::new(k, n).expect("column domain");
+            for c in 0..m {
+                let col = matrix.get_col(c).unwrap();
+                let poly = domain.interpolate(&col[0..k]).expect("interpolate");
+                for (r, cell) in col.iter().enumerate() {
+                    assert_eq!(poly.evaluate(&domain.row_point(r)), *cell, "k={} n={} row={} col={}", k, n, r, c);
+                }
+            }
+
+            // decoding from any k rows recovers the original matrix
+            for _ in 0..8 {
+                let mut matrix_opts: Vec<_> = matrix.elms.iter().cloned().map(Some).collect();
+                for i in rand::seq::index::sample(&mut rng, n, n - k) {
+                    matrix_opts[i] = None;
+                }
+                BLSFieldEncoder::reconstruct(params.clone(), &mut matrix_opts).expect("reconstruction should succeed");
+                for (i, (recovered, row)) in matrix_opts.iter().zip(&matrix.elms).enumerate() {
+                    assert_eq!(recovered.as_ref(), Some(row), "k={} n={} row={}", k, n, i);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_column_domain_fft() {
+        let mut rng = test_rng();
+        for (k, n) in [(4, 8), (5, 9), (3, 12), (2, 9), (100, 200), (512, 1024)] {
+            let domain = ColumnDomain::new(k, n).expect("column domain");
+            // every row point is in the extension domain at its index
+            for r in 0..n {
+                assert_eq!(domain.ext_domain.element(domain.ext_index(r)), domain.row_point(r), "k={} n={} row={}", k, n, r);
+            }
+
+            // the FFT parity matches evaluating the column polynomial at each parity point
+            let cols: Vec<Vec<F>> = (0..3).map(|_| (0..k).map(|_| F::rand(&mut rng)).collect()).collect();
+            let parities = domain.parity_batch(&cols).expect("parity batch");
+            for (col, parity) in cols.iter().zip(parities) {
+                let poly = domain.interpolate(col).expect("interpolate");
+                let expected: Vec<F> = (k..n).map(|r| poly.evaluate(&domain.row_point(r))).collect();
+                assert_eq!(parity, expected, "k={} n={}", k, n);
+            }
+        }
+
+        // the batched byte encoder agrees with encoding column by column
+        let params = Params{ k: 4, n: 8, m: 3 };
+        let data = BLSData::new(Data::new_random(params));
+        let mut batched = data.clone();
+        BLSEncoder::encode(&mut batched).expect("encode failed");
+        let mut by_col = data;
+        for c in 0..by_col.params().m {
+            BLSEncoder::encode_col(&mut by_col, c).expect("encode col failed");
+        }
+        assert_eq!(batched.parity, by_col.parity);
     }
 
     #[test]
@@ -171,23 +280,26 @@ mod tests {
         // setup kzg
         type P = KZG10PolyComm;
         type C = MatrixPolyComm<F,P>;
-        let srs = C::setup(degree).expect("setup should succeed");
+        let srs = C::setup(degree, &SetupSource::InsecureTest).expect("setup should succeed");
 
         // commit to its rows
         let kzg_comm = C::commit(&srs, &matrix).expect("commit_rows should succeed");
 
         assert_eq!(kzg_comm.comm_output.len(), m);
 
-        // check that each polynomial is really the original rows
+        // check that each polynomial is really the original rows,
+        // the polynomial drops the trailing zero coefficients
         for i in 0..m {
-            let row = matrix.get_row(i).unwrap();
+            let mut row = matrix.get_row(i).unwrap();
+            while row.last().is_some_and(|c| c.is_zero()) {
+                row.pop();
+            }
             let evals: Vec<_> = kzg_comm.get_poly(i).coeffs.clone();
             assert_eq!(evals, row);
         }
     }
 
-    #[test]
-    fn test_open_commitments() {
+    fn check_open_commitments<Curve: Pairing>() {
         // dimensions: 8 rows (4 parity), 8 columns
         let n = 8;
         let k = 4;
@@ -208,28 +320,28 @@ mod tests {
         // degree is the size of each row (the number of cells in a row) which equals the number of columns
         let degree = m;
         // setup kzg
-        type P = KZG10PolyComm;
-        type C = MatrixPolyComm<F,P>;
-        let srs = C::setup(degree).expect("setup should succeed");
+        type P<Curve> = KZG10PolyComm<Curve>;
+        type C<Curve> = MatrixPolyComm<Fr<Curve>, P<Curve>>;
+        let srs = C::<Curve>::setup(degree, &SetupSource::InsecureTest).expect("setup should succeed");
 
         // commit to its rows
-        let kzg_comm = C::commit(&srs, &matrix).expect("commit_rows should succeed");
+        let kzg_comm = C::<Curve>::commit(&srs, &matrix).expect("commit_rows should succeed");
 
         // verifier Part
         let vk = get_vk(&srs).unwrap();
-        let verifier_comms: Vec<Commitment<E>> = kzg_comm.comm_output.iter().map(|c|c.get_comm().clone()).collect();
+        let verifier_comms: Vec<Commitment<Curve>> = kzg_comm.comm_output.iter().map(|c|*c.get_comm()).collect();
 
         let mut rng = test_rng();
         // check all domain points
-        for row in 0..n {
+        for (row, comm) in verifier_comms.iter().enumerate() {
             let poly = kzg_comm.get_poly(row);
             for _j in 0..m {
-                let point = F::rand(&mut rng);
-                let proof = C::open(&kzg_comm, &srs, row, point.clone())
+                let point = Fr::<Curve>::rand(&mut rng);
+                let proof = C::<Curve>::open(&kzg_comm, &srs, row, point)
                     .expect("open should succeed");
-                let value: F = poly.evaluate(&point);
+                let value: Fr<Curve> = poly.evaluate(&point);
                 assert!(
-                    C::verify(&vk, &verifier_comms[row], point.clone(), value, &proof)
+                    C::<Curve>::verify(&vk, comm, point, value, &proof)
                         .expect("verify should succeed"),
                     "KZG open/verify failed for row={}, point={:?}",
                     row,
@@ -239,6 +351,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_open_commitments() {
+        check_open_commitments::<E>();
+    }
+
+    #[test]
+    fn test_open_commitments_bn254() {
+        check_open_commitments::<ark_bn254::Bn254>();
+    }
+
     #[test]
     fn test_update_col(){
         // dimensions: 8 rows (4 parity), 8 columns
@@ -270,10 +392,10 @@ mod tests {
         data.pretty_print();
 
         //data matrix [0..k) at col c must match new_col
-        for i in 0..k {
+        for (i, cell) in new_col.iter().enumerate() {
             assert_eq!(
                 data.matrix[i][c],
-                new_col[i],
+                *cell,
                 "data matrix {} at row {} should be updated", i, c
             );
         }
@@ -283,6 +405,82 @@ mod tests {
         data.pretty_print();
     }
 
+    /// write the data cells at `rows` of column `c` and check `update_parity` gives the same matrix as `encode_col`
+    fn check_update_parity<T, Enc>(encoded: &Enc::DataMatrix<T>, params: &Params, c: usize, rows: &[usize], mut new_cell: impl FnMut() -> T)
+    where
+        T: Clone + PartialEq + std::fmt::Debug,
+        Enc: Encoder<T>,
+        Enc::DataMatrix<T>: DataMatrix<T> + Clone,
+    {
+        let mut updated = encoded.clone();
+        let mut old_vals = vec![];
+        for &r in rows {
+            old_vals.push(updated.get(r, c).unwrap());
+            updated.set(r, c, new_cell()).unwrap();
+        }
+        let mut expected = updated.clone();
+        Enc::encode_col(&mut expected, c).expect("encode col");
+        Enc::update_parity(&mut updated, c, rows, &old_vals).expect("update parity");
+        for col in 0..params.m {
+            assert_eq!(updated.get_col(col).unwrap(), expected.get_col(col).unwrap(), "rows {:?} col {}", rows, col);
+        }
+    }
+
+    #[test]
+    fn test_update_parity() {
+        let mut rng = test_rng();
+        // a single cell, the first and last data rows, several rows in any order
+        let changes: [&[usize]; 4] = [&[1], &[0], &[4], &[3, 0, 2]];
+        for (k, n) in [(5, 10), (5, 7), (5, 20)] {
+            let params = Params{ k, n, m: 3 };
+
+            let mut data = Data::new_random(params.clone());
+            G8Encoder::encode(&mut data).expect("encode failed");
+            for rows in changes {
+                check_update_parity::<u8, G8Encoder<u8>>(&data, &params, 1, rows, rand::random);
+            }
+
+            let mut data = BLSData::new(Data::new_random(params.clone()));
+            BLSEncoder::encode(&mut data).expect("encode failed");
+            for rows in changes {
+                let mut updated = data.clone();
+                let mut old_vals = vec![];
+                for &r in rows {
+                    old_vals.push(updated.data.get(r, 1).unwrap());
+                    updated.data.set(r, 1, rand::random()).unwrap();
+                }
+                let mut expected = updated.clone();
+                BLSEncoder::encode_col(&mut expected, 1).expect("encode col");
+                BLSEncoder::update_parity(&mut updated, 1, rows, &old_vals).expect("update parity");
+                assert_eq!(updated.parity, expected.parity, "rows {:?}", rows);
+            }
+
+            let mut matrix = Matrix::<F>::new_random(params.clone());
+            BLSFieldEncoder::encode(&mut matrix).expect("encode failed");
+            for rows in changes {
+                check_update_parity::<F, BLSFieldEncoder<F>>(&matrix, &params, 2, rows, || F::rand(&mut rng));
+            }
+
+            let mut matrix = FatMatrix::<F>::new_random(FatParams{ params: params.clone(), cell_size: 2 });
+            BLSFatEncoder::encode(&mut matrix).expect("encode failed");
+            for rows in changes {
+                check_update_parity::<Vec<F>, BLSFatEncoder<Vec<F>>>(&matrix, &params, 0, rows, || vec![F::rand(&mut rng), F::rand(&mut rng)]);
+            }
+        }
+
+        // parity rows, repeated rows and mismatched old values are rejected
+        let params = Params{ k: 4, n: 8, m: 2 };
+        let mut matrix = Matrix::<F>::new_random(params);
+        BLSFieldEncoder::encode(&mut matrix).expect("encode failed");
+        let old = matrix.get(0, 0).unwrap();
+        assert!(BLSFieldEncoder::update_parity(&mut matrix, 0, &[4], &[old]).is_err());
+        assert!(BLSFieldEncoder::update_parity(&mut matrix, 0, &[0, 0], &[old, old]).is_err());
+        assert!(BLSFieldEncoder::update_parity(&mut matrix, 0, &[0, 1], &[old]).is_err());
+
+        // the changed cells are taken from the data part of the old encoded column
+        assert_eq!(changed_cells(&[1u8, 2, 3, 4, 9, 9], &[1, 5, 3, 0]), (vec![1, 3], vec![2, 4]));
+    }
+
     #[test]
     fn test_kzg10_update_commitments() {
         // dimensions: 8 rows (4 parity), 8 columns
@@ -307,23 +505,22 @@ mod tests {
         let degree = m;
         // setup kzg
         type P = KZG10PolyComm;
-        let srs = P::setup(degree).expect("setup should succeed");
+        let srs = P::setup(degree, &SetupSource::InsecureTest).expect("setup should succeed");
         let mut row = matrix.get_row(0).expect("get row");
         let mut com = P::commit(&srs, row.clone()).expect("commit");
 
         // Verify that row polynomial coeffs are the row data
-        for i in 0..m {
-            let row_elem = row[i].clone();
-            let eval = com.poly.coeffs[i].clone();
-            assert_eq!(eval, row_elem);
+        for (i, row_elem) in row.iter().enumerate() {
+            let eval = com.poly.coeffs[i];
+            assert_eq!(eval, *row_elem);
         }
 
-        let cell = row[0].clone();
+        let cell = row[0];
         let new_cell = cell + F::from(10u64);
 
-        P::update_commitment(&srs,&mut com,cell,new_cell.clone(),0).expect("update comm");
+        P::update_commitment(&srs,&mut com,cell,new_cell,0).expect("update comm");
 
-        let eval = com.poly.coeffs[0].clone();
+        let eval = com.poly.coeffs[0];
         assert_eq!(eval, new_cell);
 
         row[0] = new_cell;
@@ -334,8 +531,7 @@ mod tests {
 
     }
 
-    #[test]
-    fn test_update_commitments() {
+    fn check_update_commitments<Curve: Pairing>() {
         // dimensions: 8 rows (4 parity), 8 columns
         let n = 8;
         let k = 4;
@@ -352,19 +548,19 @@ mod tests {
         G8Encoder::encode(&mut data).expect("encode failed");
 
         // Build a matrix where entry (i,j) = i * m + j
-        let mut matrix = Matrix::<F>::from_data(&data);
+        let mut matrix = Matrix::<Fr<Curve>>::from_data(&data);
         println!("---------- original ------------");
         matrix.pretty_print();
 
         // degree is the size of each row (the number of cells in a row) which equals the number of columns
         let degree = m;
         // setup kzg
-        type P = KZG10PolyComm;
-        type C = MatrixPolyComm<F,P>;
-        let srs = C::setup(degree).expect("setup should succeed");
+        type P<Curve> = KZG10PolyComm<Curve>;
+        type C<Curve> = MatrixPolyComm<Fr<Curve>, P<Curve>>;
+        let srs = C::<Curve>::setup(degree, &SetupSource::InsecureTest).expect("setup should succeed");
 
         // commit to its rows
-        let mut kzg_comm = C::commit(&srs, &matrix).expect("commit_rows should succeed");
+        let mut kzg_comm = C::<Curve>::commit(&srs, &matrix).expect("commit_rows should succeed");
 
         // a column to update
         let col_idx = 1;
@@ -373,7 +569,7 @@ mod tests {
         let new_col_data: Vec<_> = old_col
             .iter()
             .take(k)                              // only look at the first k entries
-            .map(|v| *v + F::from(10u64))        // then do your +10
+            .map(|v| *v + Fr::<Curve>::from(10u64))        // then do your +10
             .collect();
 
         matrix.update_col(col_idx, &new_col_data).expect("update col");
@@ -386,18 +582,19 @@ mod tests {
         let encoded_new_col = matrix.get_col(col_idx).expect("get old col");
 
         // do the comm update
-        C::update_commitments(&srs, &mut kzg_comm, col_idx, &old_col, &encoded_new_col).expect("update comm");
+        C::<Curve>::update_commitments(&srs, &mut kzg_comm, col_idx, &old_col, &encoded_new_col).expect("update comm");
 
         // Verify that each row polynomial now evaluates to the updated matrix
         for i in 0..m {
             let row = matrix.get_row(i).unwrap();
-            // evaluate poly at each domain point and collect
-            let evals: Vec<_> = kzg_comm.get_poly(i).coeffs.clone();
+            // the coefficients are the row, up to the trimmed trailing zeros
+            let mut evals: Vec<_> = kzg_comm.get_poly(i).coeffs.clone();
+            evals.resize(row.len(), Fr::<Curve>::zero());
             assert_eq!(evals, row);
         }
 
         // === new fresh commit on updated matrix ===
-        let kzg_comm_fresh = C::commit(&srs, &matrix).expect("commit updated matrix");
+        let kzg_comm_fresh = C::<Curve>::commit(&srs, &matrix).expect("commit updated matrix");
         // Compare each row commitment
         for (i, old_lbl_comm) in kzg_comm.comm_output.iter().enumerate() {
             let updated_comm = old_lbl_comm.get_comm();
@@ -406,4 +603,1115 @@ mod tests {
         }
 
     }
+
+    #[test]
+    fn test_update_commitments() {
+        check_update_commitments::<E>();
+    }
+
+    #[test]
+    fn test_update_commitments_bn254() {
+        check_update_commitments::<ark_bn254::Bn254>();
+    }
+
+    /// a random encoded matrix, an srs for rows of up to `max_cells` cells and the row commitments
+    fn commit_encoded<P: PolyCommScheme<F>>(params: Params, max_cells: usize) -> (Matrix<F>, P::SRS, MatrixCommitOutput<F, P>) {
+        let mut matrix = Matrix::<F>::new_random(params);
+        BLSFieldEncoder::encode(&mut matrix).expect("encode failed");
+        let srs = MatrixPolyComm::<F, P>::setup(max_cells, &SetupSource::InsecureTest).expect("setup should succeed");
+        let comm = MatrixPolyComm::<F, P>::commit(&srs, &matrix).expect("commit should succeed");
+        (matrix, srs, comm)
+    }
+
+    /// append, delete and truncate columns of an encoded matrix, the updated commitments must match a fresh commit
+    fn check_resize_commitments<P: PolyCommScheme<F, CommitOutput = KZG10CommitOutput>>() {
+        let (k, n, m) = (4, 8, 4);
+        let (mut matrix, srs, mut comm) = commit_encoded::<P>(Params{ k, n, m }, 8);
+        let check = |matrix: &Matrix<F>, comm: &MatrixCommitOutput<F, P>| {
+            let fresh = MatrixPolyComm::<F, P>::commit(&srs, matrix).expect("commit should succeed");
+            assert_eq!(comm.comm_output.len(), fresh.comm_output.len());
+            for (i, (updated, fresh)) in comm.comm_output.iter().zip(&fresh.comm_output).enumerate() {
+                assert_eq!(updated.get_comm(), fresh.get_comm(), "row {} commitment", i);
+                assert_eq!(updated.get_poly().polynomial(), fresh.get_poly().polynomial(), "row {} poly", i);
+            }
+        };
+
+        // append two columns (the second one all zero), they are encoded then committed with the next powers
+        let rng = &mut test_rng();
+        let new_cols = vec![(0..k).map(|_| F::rand(rng)).collect(), vec![F::zero(); k]];
+        matrix.append_cols(&new_cols).expect("append cols should succeed");
+        assert_eq!(matrix.params.m, m + 2);
+        for c in m..m + 2 {
+            BLSFieldEncoder::encode_col(&mut matrix, c).expect("encode col failed");
+        }
+        let encoded: Vec<Vec<F>> = (m..m + 2).map(|c| matrix.get_col(c).unwrap()).collect();
+        MatrixPolyComm::<F, P>::append_commitments(&srs, &mut comm, m, &encoded).expect("append commitments should succeed");
+        check(&matrix, &comm);
+
+        // delete a column in the middle, the later columns shift left
+        let old_cols: Vec<Vec<F>> = (1..matrix.params.m).map(|c| matrix.get_col(c).unwrap()).collect();
+        let shifted = matrix.get_col(2).unwrap();
+        matrix.delete_col(1).expect("delete col should succeed");
+        assert_eq!(matrix.params.m, m + 1);
+        assert_eq!(matrix.get_col(1).unwrap(), shifted);
+        MatrixPolyComm::<F, P>::delete_col_commitments(&srs, &mut comm, 1, &old_cols).expect("delete col commitments should succeed");
+        check(&matrix, &comm);
+
+        // truncate to the first two columns
+        let old_cols: Vec<Vec<F>> = (2..matrix.params.m).map(|c| matrix.get_col(c).unwrap()).collect();
+        matrix.truncate_cols(2).expect("truncate cols should succeed");
+        assert_eq!(matrix.params.m, 2);
+        MatrixPolyComm::<F, P>::truncate_commitments(&srs, &mut comm, 2, &old_cols).expect("truncate commitments should succeed");
+        check(&matrix, &comm);
+
+        // the columns stay codewords
+        for c in 0..matrix.params.m {
+            assert!(BLSFieldEncoder::is_codeword(matrix.params.clone(), &matrix.get_col(c).unwrap()).expect("is codeword should succeed"));
+        }
+
+        // the rows can't outgrow the srs, nor the matrix lose all of its columns
+        let too_many = vec![vec![F::zero(); n]; P::max_cells(&srs) - 1];
+        assert!(MatrixPolyComm::<F, P>::append_commitments(&srs, &mut comm, 2, &too_many).is_err());
+        assert!(matrix.truncate_cols(0).is_err());
+        assert!(matrix.truncate_cols(3).is_err());
+    }
+
+    #[test]
+    fn test_resize_commitments() {
+        check_resize_commitments::<KZG10PolyComm>();
+    }
+
+    #[test]
+    fn test_resize_lagrange_commitments() {
+        check_resize_commitments::<KZG10LagrangePolyComm>();
+    }
+
+    #[test]
+    fn test_resize_data() {
+        let params = Params{ k: 4, n: 8, m: 3 };
+        let new_cols: Vec<Vec<u8>> = vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8]];
+
+        // the appended columns are encoded on their own
+        let data = Data::new_random(params.clone());
+        let mut g8 = data.clone();
+        G8Encoder::encode(&mut g8).expect("encode failed");
+        g8.append_cols(&new_cols).expect("append cols should succeed");
+        for c in 3..5 {
+            G8Encoder::encode_col(&mut g8, c).expect("encode col failed");
+        }
+        g8.delete_col(0).expect("delete col should succeed");
+        g8.truncate_cols(3).expect("truncate cols should succeed");
+
+        // the same as encoding the resized data from scratch
+        let mut expected = data;
+        expected.append_cols(&new_cols).expect("append cols should succeed");
+        expected.delete_col(0).expect("delete col should succeed");
+        expected.truncate_cols(3).expect("truncate cols should succeed");
+        G8Encoder::encode(&mut expected).expect("encode failed");
+        assert_eq!(g8.matrix, expected.matrix);
+        assert_eq!(g8.params.m, 3);
+        assert!(g8.matrix.iter().all(|row| row.len() == 3));
+
+        // fat cells move with all of their lanes
+        let params = Params{ k: 4, n: 8, m: 3 };
+        let mut fat = FatMatrix::<F>::new_random(FatParams{ params, cell_size: 2 });
+        let last = fat.get_col(2).unwrap();
+        fat.delete_col(1).expect("delete col should succeed");
+        assert_eq!(fat.get_col(1).unwrap(), last);
+        let rng = &mut test_rng();
+        let new_col: Vec<Vec<F>> = (0..4).map(|_| vec![F::rand(rng), F::rand(rng)]).collect();
+        fat.append_cols(std::slice::from_ref(&new_col)).expect("append cols should succeed");
+        BLSFatEncoder::encode_col(&mut fat, 2).expect("encode col failed");
+        assert_eq!(fat.get_col(2).unwrap()[..4], new_col[..]);
+        assert!(BLSFatEncoder::is_codeword(fat.params.clone(), &fat.get_col(2).unwrap()).expect("is codeword should succeed"));
+    }
+
+    #[test]
+    fn test_update_commitments_batch() {
+        // dimensions: 8 rows (4 parity), 8 columns
+        let n = 8;
+        let k = 4;
+        let m = 8;
+
+        let params = Params {
+            k,
+            n,
+            m,
+        };
+        let mut matrix = Matrix::<F>::new_random(params);
+        BLSFieldEncoder::encode(&mut matrix).expect("encode failed");
+
+        // setup kzg
+        type P = KZG10PolyComm;
+        type C = MatrixPolyComm<F,P>;
+        let srs = C::setup(m, &SetupSource::InsecureTest).expect("setup should succeed");
+        let mut kzg_comm = C::commit(&srs, &matrix).expect("commit_rows should succeed");
+
+        // rewrite a contiguous range of columns
+        let mut updates = vec![];
+        for col_idx in 2..6 {
+            let old_col = matrix.get_col(col_idx).expect("get old col");
+            let new_col_data: Vec<_> = old_col
+                .iter()
+                .take(k)
+                .map(|v| *v + F::from(col_idx as u64))
+                .collect();
+            matrix.update_col(col_idx, &new_col_data).expect("update col");
+            BLSFieldEncoder::encode_col(&mut matrix, col_idx).expect("encode col");
+            let new_col = matrix.get_col(col_idx).expect("get new col");
+            updates.push(ColumnUpdate::new(col_idx, old_col, new_col));
+        }
+
+        // do the comm update in one call
+        C::update_commitments_batch(&srs, &mut kzg_comm, &updates).expect("update comm");
+
+        // compare with a fresh commit on the updated matrix
+        let kzg_comm_fresh = C::commit(&srs, &matrix).expect("commit updated matrix");
+        for i in 0..n {
+            assert_eq!(kzg_comm.get_poly(i).coeffs, matrix.get_row(i).unwrap());
+            assert_eq!(kzg_comm.get_comm(i), kzg_comm_fresh.get_comm(i), "Row commitment mismatch at row {}", i);
+        }
+
+        // the same column twice in one batch is rejected
+        let repeated = vec![updates[0].clone(), updates[0].clone()];
+        assert!(C::update_commitments_batch(&srs, &mut kzg_comm, &repeated).is_err());
+    }
+
+    #[test]
+    fn test_batch_open_commitments() {
+        // dimensions: 8 rows (4 parity), 8 columns
+        let n = 8;
+        let k = 4;
+        let m = 8;
+
+        let params = Params {
+            k,
+            n,
+            m,
+        };
+        let mut matrix = Matrix::<F>::new_random(params);
+        BLSFieldEncoder::encode(&mut matrix).expect("encode failed");
+
+        // setup kzg
+        type P = KZG10PolyComm;
+        type C = MatrixPolyComm<F,P>;
+        let srs = C::setup(m, &SetupSource::InsecureTest).expect("setup should succeed");
+        let kzg_comm = C::commit(&srs, &matrix).expect("commit_rows should succeed");
+
+        // verifier Part
+        let vk = get_vk(&srs).unwrap();
+        let verifier_comms: Vec<Commitment<E>> = kzg_comm.comm_output.iter().map(|c|*c.get_comm()).collect();
+
+        // sampled rows, shared point and aggregation challenge
+        let mut rng = test_rng();
+        let rows = [0, 3, 5, 6];
+        let point = F::rand(&mut rng);
+        let challenge = F::rand(&mut rng);
+
+        let proof = C::batch_open(&kzg_comm, &srs, &rows, point, challenge).expect("batch open should succeed");
+        let values: Vec<F> = rows.iter().map(|&r| kzg_comm.get_poly(r).evaluate(&point)).collect();
+        let comms: Vec<&Commitment<E>> = rows.iter().map(|&r| &verifier_comms[r]).collect();
+        assert!(C::batch_verify(&vk, &comms, point, &values, challenge, &proof).expect("batch verify should succeed"));
+
+        // a wrong value for any row must be rejected
+        let mut wrong_values = values.clone();
+        wrong_values[2] += F::from(1u64);
+        assert!(!C::batch_verify(&vk, &comms, point, &wrong_values, challenge, &proof).expect("batch verify should succeed"));
+
+        // a missing value is rejected and opening a row out of bounds fails
+        assert!(!C::batch_verify(&vk, &comms, point, &values[1..], challenge, &proof).expect("batch verify should succeed"));
+        assert!(C::batch_open(&kzg_comm, &srs, &[0, n], point, challenge).is_err());
+    }
+
+    fn check_merkle_tree<H: MerkleHasher + Clone>(hasher: H) {
+        // odd number of rows so that some nodes have no sibling
+        let n = 7;
+        let k = 3;
+        let m = 8;
+
+        let params = Params {
+            k,
+            n,
+            m,
+        };
+        let mut matrix = Matrix::<F>::new_random(params);
+        BLSFieldEncoder::encode(&mut matrix).expect("encode failed");
+
+        type P = KZG10PolyComm;
+        type C = MatrixPolyComm<F,P>;
+        let srs = C::setup(m, &SetupSource::InsecureTest).expect("setup should succeed");
+        let mut kzg_comm = C::commit(&srs, &matrix).expect("commit_rows should succeed");
+        let comms: Vec<Commitment<E>> = kzg_comm.comm_output.iter().map(|c|*c.get_comm()).collect();
+
+        let mut tree = MerkleTree::from_commitments(hasher, &comms).expect("build tree");
+        let root = tree.root();
+
+        // every row commitment is included under the root
+        for row in 0..n {
+            let proof = tree.prove(row).expect("prove");
+            assert!(MerkleTree::verify_commitment(&tree.hasher, &root, n, &comms[row], &proof).unwrap());
+            // but not at another position
+            let other = (row + 1) % n;
+            assert!(!MerkleTree::verify_commitment(&tree.hasher, &root, n, &comms[other], &proof).unwrap());
+        }
+
+        // the last leaf has no sibling, its proof must not verify at the out of range index n
+        // (same path once the missing sibling is skipped), nor with its sibling pattern changed
+        let last = tree.prove(n - 1).expect("prove");
+        assert!(last.siblings[0].is_none());
+        let out_of_range = MerkleProof{ index: n, ..last.clone() };
+        assert!(!MerkleTree::verify_commitment(&tree.hasher, &root, n, &comms[n - 1], &out_of_range).unwrap());
+        assert!(!MerkleTree::verify_commitment(&tree.hasher, &root, n + 1, &comms[n - 1], &out_of_range).unwrap());
+        let mut extra = last.clone();
+        extra.siblings.push(None);
+        assert!(!MerkleTree::verify_commitment(&tree.hasher, &root, n, &comms[n - 1], &extra).unwrap());
+        let mut filled = last.clone();
+        filled.siblings[0] = filled.siblings[1].clone();
+        assert!(!MerkleTree::verify_commitment(&tree.hasher, &root, n, &comms[n - 1], &filled).unwrap());
+
+        // update a single cell so that only one row commitment changes
+        let col_idx = 2;
+        let old_col = matrix.get_col(col_idx).unwrap();
+        let mut new_col = old_col.clone();
+        new_col[1] += F::from(7u64);
+        C::update_commitments(&srs, &mut kzg_comm, col_idx, &old_col, &new_col).expect("update comm");
+        let new_comms: Vec<Commitment<E>> = kzg_comm.comm_output.iter().map(|c|*c.get_comm()).collect();
+
+        // incremental update matches a freshly built tree
+        tree.update_commitments(&[1], &new_comms).expect("update tree");
+        let fresh_root = MerkleTree::from_commitments(tree.hasher.clone(), &new_comms).expect("build tree").root();
+        assert_ne!(tree.root(), root);
+        assert_eq!(tree.root(), fresh_root);
+        let proof = tree.prove(1).expect("prove");
+        assert!(MerkleTree::verify_commitment(&tree.hasher, &tree.root(), n, &new_comms[1], &proof).unwrap());
+    }
+
+    #[test]
+    fn test_merkle_tree_sha256() {
+        check_merkle_tree(Sha256Hasher);
+    }
+
+    #[test]
+    fn test_merkle_tree_poseidon() {
+        check_merkle_tree(PoseidonHasher::new());
+    }
+
+    #[test]
+    fn test_protocol() {
+        // dimensions: 8 rows (4 parity), 8 columns
+        let n = 8;
+        let k = 4;
+        let m = 8;
+
+        let params = Params {
+            k,
+            n,
+            m,
+        };
+        let data = Matrix::<F>::new_random(params);
+
+        type P = KZG10PolyComm;
+        type C = MatrixPolyComm<F,P>;
+        let srs = C::setup(m, &SetupSource::InsecureTest).expect("setup should succeed");
+
+        // run the SP on its own thread
+        let (client_end, sp_end) = channel();
+        let sp = StorageProvider::new(sp_end, srs.clone());
+        let handle = std::thread::spawn(move || sp.serve());
+
+        let mut client = Client::new(client_end);
+        client.upload(&srs, data.clone()).expect("upload");
+        for seed in 0..3 {
+            assert!(client.challenge(seed, 3).expect("challenge"), "challenge failed for seed {}", seed);
+        }
+
+        // update a column and check the client ends up with the commitments of the updated matrix
+        let col_idx = 3;
+        let new_col: Vec<F> = (0..k).map(|i| F::from(i as u64)).collect();
+        assert!(client.update(col_idx, new_col.clone(), 7).expect("update"));
+
+        let mut expected = data;
+        BLSFieldEncoder::encode(&mut expected).expect("encode failed");
+        expected.update_col(col_idx, &new_col).expect("update col");
+        BLSFieldEncoder::encode_col(&mut expected, col_idx).expect("encode col");
+        let expected_comm = C::commit(&srs, &expected).expect("commit");
+        for i in 0..n {
+            assert_eq!(&client.comms()[i], expected_comm.get_comm(i));
+        }
+        assert!(client.challenge(42, n).expect("challenge"));
+
+        // hang up and let the SP finish
+        drop(client);
+        handle.join().unwrap().expect("serve");
+    }
+
+    #[test]
+    fn test_update_proof() {
+        // dimensions: 8 rows (4 parity), 8 columns
+        let n = 8;
+        let k = 4;
+        let m = 8;
+
+        let params = Params {
+            k,
+            n,
+            m,
+        };
+        let mut matrix = Matrix::<F>::new_random(params.clone());
+        BLSFieldEncoder::encode(&mut matrix).expect("encode failed");
+
+        type P = KZG10PolyComm;
+        type C = MatrixPolyComm<F,P>;
+        let srs = C::setup(m, &SetupSource::InsecureTest).expect("setup should succeed");
+        let vk = get_vk(&srs).unwrap();
+        let old_comm = C::commit(&srs, &matrix).expect("commit_rows should succeed");
+
+        // SP side: update a column, re-encode it and update the commitments
+        let col_idx = 6;
+        let old_col = matrix.get_col(col_idx).expect("get old col");
+        let new_col_data: Vec<F> = (0..k).map(|i| F::from(100 + i as u64)).collect();
+        matrix.update_col(col_idx, &new_col_data).expect("update col");
+        BLSFieldEncoder::encode_col(&mut matrix, col_idx).expect("encode col");
+        let new_col = matrix.get_col(col_idx).expect("get new col");
+        let mut new_comm = old_comm.clone();
+        C::update_commitments(&srs, &mut new_comm, col_idx, &old_col, &new_col).expect("update comm");
+
+        // verifier picks the point and challenge after seeing the new commitments
+        let mut rng = test_rng();
+        let point = F::rand(&mut rng);
+        let challenge = F::rand(&mut rng);
+        let update = ColumnUpdate::new(col_idx, old_col, new_col.clone());
+        let proof = C::prove_update(&srs, &old_comm, &new_comm, &update, point, challenge)
+            .expect("prove update");
+
+        let old_comms: Vec<&Commitment<E>> = (0..n).map(|i| old_comm.get_comm(i)).collect();
+        let new_comms: Vec<&Commitment<E>> = (0..n).map(|i| new_comm.get_comm(i)).collect();
+        assert!(C::verify_update::<BLSFieldEncoder<F>>(&vk, &params, &old_comms, &new_comms, &proof, point, challenge).unwrap());
+
+        // the commitments did not change at another column
+        let mut wrong_col = proof.clone();
+        wrong_col.col_idx = 2;
+        assert!(!C::verify_update::<BLSFieldEncoder<F>>(&vk, &params, &old_comms, &new_comms, &wrong_col, point, challenge).unwrap());
+
+        // a delta that is not a codeword is rejected
+        let mut not_codeword = proof.clone();
+        not_codeword.delta[n - 1] += F::from(1u64);
+        assert!(!C::verify_update::<BLSFieldEncoder<F>>(&vk, &params, &old_comms, &new_comms, &not_codeword, point, challenge).unwrap());
+
+        // a commitment that was not updated by the delta is rejected
+        let mut tampered = new_comm.clone();
+        P::update_commitment(&srs, &mut tampered.comm_output[0], new_col[0], new_col[0] + F::from(1u64), 0).expect("update comm");
+        let tampered_comms: Vec<&Commitment<E>> = (0..n).map(|i| tampered.get_comm(i)).collect();
+        assert!(!C::verify_update::<BLSFieldEncoder<F>>(&vk, &params, &old_comms, &tampered_comms, &proof, point, challenge).unwrap());
+    }
+
+    #[test]
+    fn test_fat_matrix() {
+        let k = 4;
+        let n = 8;
+        let m = 4;
+        let cell_size = 3;
+        let params = Params{ k, n, m };
+
+        // encode and check every lane is a codeword
+        let mut matrix: FatMatrix<F> = FatMatrix::new_random(FatParams{ params: params.clone(), cell_size });
+        BLSFatEncoder::encode(&mut matrix).expect("encode failed");
+        for c in 0..m {
+            assert!(BLSFatEncoder::is_codeword(params.clone(), &matrix.get_col(c).unwrap()).expect("is codeword should succeed"));
+        }
+
+        // reconstruct after losing n - k rows
+        let mut rows: Vec<Option<Vec<Vec<F>>>> = (0..n).map(|r| matrix.get_row(r).ok()).collect();
+        for r in [0, 2, 5, 7] {
+            rows[r] = None;
+        }
+        BLSFatEncoder::reconstruct(params.clone(), &mut rows).expect("reconstruct should succeed");
+        for (r, recovered) in rows.iter().enumerate() {
+            assert_eq!(recovered.as_ref().unwrap(), &matrix.get_row(r).unwrap());
+        }
+
+        // commit, update a column, then open a row at a random point
+        let srs = MatrixPolyComm::<F, KZG10PolyComm>::setup_fat(m, cell_size, &SetupSource::InsecureTest).expect("setup fat should succeed");
+        let mut comm = MatrixPolyComm::<F, KZG10PolyComm>::commit_fat(&srs, &matrix).expect("commit fat should succeed");
+
+        let rng = &mut test_rng();
+        let c = 1;
+        let old_col = matrix.get_col(c).unwrap();
+        let new_col: Vec<Vec<F>> = (0..k)
+            .map(|_| (0..cell_size).map(|_| F::rand(rng)).collect())
+            .collect();
+        matrix.update_col(c, &new_col).expect("update col should succeed");
+        BLSFatEncoder::encode_col(&mut matrix, c).expect("encode col failed");
+        let encoded_new_col = matrix.get_col(c).unwrap();
+        MatrixPolyComm::<F, KZG10PolyComm>::update_fat_commitments(
+            &srs, &mut comm, cell_size, c, &old_col, &encoded_new_col,
+        ).expect("update fat commitments should succeed");
+
+        let expected = MatrixPolyComm::<F, KZG10PolyComm>::commit_fat(&srs, &matrix).expect("commit fat should succeed");
+        for r in 0..n {
+            assert_eq!(comm.comm_output[r].get_comm(), expected.comm_output[r].get_comm());
+        }
+
+        let point = F::rand(rng);
+        let row = 5;
+        let proof = MatrixPolyComm::<F, KZG10PolyComm>::open(&comm, &srs, row, point).expect("open should succeed");
+        let value = comm.get_poly(row).evaluate(&point);
+        assert!(MatrixPolyComm::<F, KZG10PolyComm>::verify(&get_vk(&srs).expect("get vk should succeed"), comm.comm_output[row].get_comm(), point, value, &proof).expect("verify should succeed"));
+    }
+
+    fn check_packing<Fp: ark_ff::PrimeField>(byte_cols: usize) {
+        let params = Params{ k: 4, n: 8, m: byte_cols };
+        let mut data = Data::new_random(params.clone());
+        // parity rows are not packed
+        for r in params.k..params.n {
+            data.matrix[r].fill(0);
+        }
+
+        let matrix: Matrix<Fp> = Matrix::from_data_packed(&data);
+        assert_eq!(matrix.params.m, byte_cols.div_ceil(packed_bytes_per_elm::<Fp>()));
+        let unpacked = matrix.to_data_packed(byte_cols).expect("to data packed should succeed");
+        assert_eq!(unpacked.params.m, byte_cols);
+        assert_eq!(unpacked.matrix, data.matrix);
+    }
+
+    #[test]
+    fn test_packed_from_data() {
+        assert_eq!(packed_bytes_per_elm::<F>(), 31);
+        assert_eq!(packed_bytes_per_elm::<ark_bn254::Fr>(), 31);
+
+        for byte_cols in [1, 30, 31, 32, 62, 100] {
+            check_packing::<F>(byte_cols);
+            check_packing::<ark_bn254::Fr>(byte_cols);
+        }
+
+        // the packed matrix can be encoded and reconstructed like any field matrix
+        let params = Params{ k: 4, n: 8, m: 45 };
+        let data = Data::new_random(params.clone());
+        let mut matrix: Matrix<F> = Matrix::from_data_packed(&data);
+        BLSFieldEncoder::encode(&mut matrix).expect("encode failed");
+        let mut rows: Vec<Option<Vec<F>>> = (0..params.n).map(|r| matrix.get_row(r).ok()).collect();
+        for row in rows.iter_mut().take(params.n - params.k) {
+            *row = None;
+        }
+        BLSFieldEncoder::reconstruct(matrix.params.clone(), &mut rows).expect("reconstruct should succeed");
+        let mut recovered = matrix.clone();
+        recovered.elms = rows.into_iter().map(|row| row.unwrap()).collect();
+        let unpacked = recovered.to_data_packed(params.m).expect("to data packed should succeed");
+        assert_eq!(unpacked.matrix[0..params.k], data.matrix[0..params.k]);
+
+        // the wrong byte length is rejected
+        assert!(recovered.to_data_packed(params.m + 31).is_err());
+    }
+
+    #[test]
+    fn test_data_from_bytes() {
+        let k = 4;
+        let n = 8;
+        for len in [0, 1, 3, 4, 5, 17, 100] {
+            let bytes: Vec<u8> = (0..len).map(|i| (i * 7 + 1) as u8).collect();
+            let (mut data, manifest) = Data::from_reader(bytes.as_slice(), k, n).expect("from reader should succeed");
+            assert_eq!(manifest.original_len, len);
+            assert!(data.params.capacity() >= len);
+            assert!(data.params.capacity() < len + k || len == 0);
+
+            // each column holds a contiguous range of bytes
+            if len >= k {
+                assert_eq!(data.get_col(0).unwrap()[0..k], bytes[0..k]);
+            }
+
+            // lose parity-many rows and recover the original bytes
+            G8Encoder::encode(&mut data).expect("encode failed");
+            let mut shards: Vec<Option<Vec<u8>>> = data.matrix.iter().cloned().map(Some).collect();
+            for shard in shards.iter_mut().take(n - k) {
+                *shard = None;
+            }
+            G8Encoder::reconstruct(data.params.clone(), &mut shards).expect("reconstruct should succeed");
+            data.matrix = shards.into_iter().map(|row| row.unwrap()).collect();
+            assert_eq!(data.into_bytes(&manifest).expect("into bytes should succeed"), bytes);
+        }
+
+        // explicit params must be large enough
+        let bytes = vec![1u8; 33];
+        assert!(Data::from_bytes_with_params(&bytes, Params{ k, n, m: 8 }).is_err());
+        let (data, manifest) = Data::from_bytes_with_params(&bytes, Params{ k, n, m: 9 }).expect("from bytes with params should succeed");
+        let wrong = Manifest{ params: Params{ k, n, m: 10 }, ..manifest.clone() };
+        assert!(data.clone().into_bytes(&wrong).is_err());
+        assert_eq!(data.into_bytes(&manifest).expect("into bytes should succeed"), bytes);
+    }
+
+    #[test]
+    fn test_serialization() {
+        let params = Params{ k: 4, n: 8, m: 4 };
+        let (data, manifest) = Data::from_bytes(&[7u8; 13], params.k, params.n).expect("from bytes should succeed");
+        let (matrix, srs, comm) = commit_encoded::<KZG10PolyComm>(params.clone(), params.m);
+        let proof = MatrixPolyComm::<F, KZG10PolyComm>::open(&comm, &srs, 1, F::from(3u8)).expect("open should succeed");
+
+        for compress in [Compress::Yes, Compress::No] {
+            let decoded: Data<u8> = from_bytes(&to_bytes(&data, compress).expect("to bytes should succeed")).expect("from bytes should succeed");
+            assert_eq!(decoded.matrix, data.matrix);
+            let decoded: Manifest = from_bytes(&to_bytes(&manifest, compress).expect("to bytes should succeed")).expect("from bytes should succeed");
+            assert_eq!(decoded.original_len, manifest.original_len);
+            let decoded: Matrix<F> = from_bytes(&to_bytes(&matrix, compress).expect("to bytes should succeed")).expect("from bytes should succeed");
+            assert_eq!(decoded.elms, matrix.elms);
+
+            let bytes = to_bytes(&comm, compress).expect("to bytes should succeed");
+            assert_eq!(peek_kind(&bytes).expect("peek kind should succeed"), Kind::MatrixCommitOutput);
+            let decoded: crate::traits::MatrixCommitOutput<F, KZG10PolyComm> = from_bytes(&bytes).expect("from bytes should succeed");
+            for r in 0..params.n {
+                assert_eq!(decoded.get_comm(r), comm.get_comm(r));
+                assert_eq!(decoded.get_poly(r).polynomial(), comm.get_poly(r).polynomial());
+            }
+
+            let decoded: ark_poly_commit::kzg10::Proof<E> = from_bytes(&to_bytes(&proof, compress).expect("to bytes should succeed")).expect("from bytes should succeed");
+            assert_eq!(decoded, proof);
+            let decoded: KZG10SRS = from_bytes(&to_bytes(&srs, compress).expect("to bytes should succeed")).expect("from bytes should succeed");
+            assert_eq!(decoded.powers_of_g, srs.powers_of_g);
+
+            let challenge = Challenge::from_seed(7, params.n, 3).expect("from seed should succeed");
+            let decoded: Challenge = from_bytes(&to_bytes(&challenge, compress).expect("to bytes should succeed")).expect("from bytes should succeed");
+            assert_eq!(decoded, challenge);
+            let values = vec![comm.get_poly(1).evaluate(&F::from(3u8))];
+            let decoded: Vec<F> = from_bytes(&to_bytes(&values, compress).expect("to bytes should succeed")).expect("from bytes should succeed");
+            assert_eq!(decoded, values);
+            let bytes = to_bytes(&vec![proof], compress).expect("to bytes should succeed");
+            assert_eq!(peek_kind(&bytes).expect("peek kind should succeed"), Kind::Proofs);
+            let decoded: Vec<ark_poly_commit::kzg10::Proof<E>> = from_bytes(&bytes).expect("from bytes should succeed");
+            assert_eq!(decoded, vec![proof]);
+        }
+
+        // wrong kind, version, trailing bytes and inconsistent shapes are rejected
+        let bytes = to_bytes(&proof, Compress::Yes).expect("to bytes should succeed");
+        assert!(from_bytes::<Matrix<F>>(&bytes).is_err());
+        let mut bad_version = bytes.clone();
+        bad_version[4] += 1;
+        assert!(from_bytes::<ark_poly_commit::kzg10::Proof<E>>(&bad_version).is_err());
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(from_bytes::<ark_poly_commit::kzg10::Proof<E>>(&trailing).is_err());
+        let mut bad_shape = matrix.clone();
+        bad_shape.elms.pop();
+        assert!(from_bytes::<Matrix<F>>(&to_bytes(&bad_shape, Compress::Yes).expect("to bytes should succeed")).is_err());
+    }
+
+    #[test]
+    fn test_load_srs() {
+        let dir = std::env::temp_dir().join(format!("dde-srs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create dir all should succeed");
+        let hex = |p: &dyn Fn(&mut Vec<u8>)| -> String {
+            let mut bytes = vec![];
+            p(&mut bytes);
+            bytes.iter().map(|b| format!("{:02x}", b)).collect()
+        };
+
+        // an arkworks serialized srs is truncated to the requested degree
+        let degree = 8;
+        let full = KZG10PolyComm::<E>::setup(16, &SetupSource::InsecureTest).expect("setup should succeed");
+        let path = dir.join("srs.bin");
+        let mut bytes = vec![];
+        full.serialize_compressed(&mut bytes).expect("serialize compressed should succeed");
+        std::fs::write(&path, &bytes).expect("write should succeed");
+        let source = SetupSource::File { path: path.clone(), format: SrsFormat::Arkworks };
+        let srs = KZG10PolyComm::<E>::setup(degree, &source).expect("setup should succeed");
+        assert_eq!(srs.powers_of_g[..], full.powers_of_g[..=degree]);
+        assert!(KZG10PolyComm::<E>::setup(17, &source).is_err());
+
+        // a tampered power breaks the pairing check
+        let mut tampered = full.clone();
+        tampered.powers_of_g[3] = tampered.powers_of_g[4];
+        let mut bytes = vec![];
+        tampered.serialize_compressed(&mut bytes).expect("serialize compressed should succeed");
+        std::fs::write(&path, &bytes).expect("write should succeed");
+        assert!(load_srs::<E>(&path, SrsFormat::Arkworks, degree).is_err());
+
+        // the ceremony points use the zcash encoding, e.g. the first monomial point is the generator
+        let generator = "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb";
+        assert_eq!(hex(&|b| ark_bls12_381::G1Affine::generator().serialize_compressed(b).unwrap()), generator);
+
+        // the same powers in the Ethereum ceremony format, without gamma powers
+        let num_g1 = 16;
+        let mut lines = vec![num_g1.to_string(), "2".to_string()];
+        for p in &full.powers_of_g[..num_g1] {
+            // the Lagrange form points are skipped by the loader
+            lines.push(hex(&|b| p.serialize_compressed(b).unwrap()));
+        }
+        lines.push(hex(&|b| full.h.serialize_compressed(b).unwrap()));
+        lines.push(hex(&|b| full.beta_h.serialize_compressed(b).unwrap()));
+        for p in &full.powers_of_g[..num_g1] {
+            lines.push(hex(&|b| p.serialize_compressed(b).unwrap()));
+        }
+        let path = dir.join("trusted_setup.txt");
+        std::fs::write(&path, lines.join("\n")).expect("write should succeed");
+        let source = SetupSource::File { path, format: SrsFormat::EthereumCeremony };
+        let srs = MatrixPolyComm::<F, KZG10PolyComm>::setup(degree, &source).expect("setup should succeed");
+        assert_eq!(srs.powers_of_g[..], full.powers_of_g[..=degree]);
+        assert!(srs.powers_of_gamma_g.is_empty());
+
+        // a corrupted, non-ASCII point line is an error
+        let first_monomial = lines.len() - num_g1;
+        lines[first_monomial] = format!("a{}a", "é".repeat(lines[first_monomial].len() / 2 - 1));
+        let corrupted = dir.join("corrupted_setup.txt");
+        std::fs::write(&corrupted, lines.join("\n")).expect("write should succeed");
+        assert!(load_srs::<E>(&corrupted, SrsFormat::EthereumCeremony, degree).is_err());
+        assert_eq!(decode_hex("0x00aBff").expect("decode hex should succeed"), vec![0x00, 0xab, 0xff]);
+        for bad in ["aéa", "abc", "+f", "0g"] {
+            assert!(decode_hex(bad).is_err(), "{:?}", bad);
+        }
+
+        // commit, open and update work with the ceremony srs
+        let params = Params{ k: 4, n: 8, m: degree };
+        let mut matrix: Matrix<F> = Matrix::new_random(params.clone());
+        BLSFieldEncoder::encode(&mut matrix).expect("encode failed");
+        let comm = MatrixPolyComm::<F, KZG10PolyComm>::commit(&srs, &matrix).expect("commit should succeed");
+        let point = F::from(5u8);
+        let proof = MatrixPolyComm::<F, KZG10PolyComm>::open(&comm, &srs, 2, point).expect("open should succeed");
+        let value = comm.get_poly(2).evaluate(&point);
+        assert!(MatrixPolyComm::<F, KZG10PolyComm>::verify(&get_vk(&srs).expect("get vk should succeed"), comm.get_comm(2), point, value, &proof).expect("verify should succeed"));
+
+        std::fs::remove_dir_all(&dir).expect("remove dir all should succeed");
+    }
+
+    #[test]
+    fn test_hiding_commitments() {
+        type P = KZG10PolyComm;
+        type C = MatrixPolyComm<F,P>;
+        let (k, n, m) = (4, 8, 8);
+        let hiding_bound = 2;
+        let params = Params{ k, n, m };
+        let (mut matrix, srs, plain) = commit_encoded::<P>(params.clone(), m);
+
+        let rng = &mut StdRng::from_entropy();
+        let vk = get_vk(&srs).expect("get vk should succeed");
+        let old_comm = C::commit_hiding(&srs, &matrix, hiding_bound, rng).expect("commit hiding should succeed");
+        for r in 0..n {
+            assert_ne!(old_comm.get_comm(r), plain.get_comm(r));
+        }
+
+        // openings of hidden commitments verify, also aggregated
+        let point = F::rand(rng);
+        let proof = C::open(&old_comm, &srs, 3, point).expect("open should succeed");
+        assert!(proof.random_v.is_some());
+        assert!(C::verify(&vk, old_comm.get_comm(3), point, old_comm.get_poly(3).evaluate(&point), &proof).expect("verify should succeed"));
+        let rows = [0, 5, 6];
+        let challenge = F::rand(rng);
+        let values: Vec<F> = rows.iter().map(|r| old_comm.get_poly(*r).evaluate(&point)).collect();
+        let proof = C::batch_open(&old_comm, &srs, &rows, point, challenge).expect("batch open should succeed");
+        let comms: Vec<&Commitment<E>> = rows.iter().map(|r| old_comm.get_comm(*r)).collect();
+        assert!(C::batch_verify(&vk, &comms, point, &values, challenge, &proof).expect("batch verify should succeed"));
+
+        // updating cells keeps the blinding, rerandomizing replaces it
+        let col_idx = 1;
+        let old_col = matrix.get_col(col_idx).unwrap();
+        matrix.update_col(col_idx, &(0..k).map(|_| F::rand(rng)).collect::<Vec<_>>()).expect("update col should succeed");
+        BLSFieldEncoder::encode_col(&mut matrix, col_idx).expect("encode col failed");
+        let new_col = matrix.get_col(col_idx).unwrap();
+        let mut new_comm = old_comm.clone();
+        C::update_commitments(&srs, &mut new_comm, col_idx, &old_col, &new_col).expect("update commitments should succeed");
+        let mut new_plain = plain.clone();
+        C::update_commitments(&srs, &mut new_plain, col_idx, &old_col, &new_col).expect("update commitments should succeed");
+        for r in 0..n {
+            let blinding = old_comm.get_comm(r).0 - plain.get_comm(r).0;
+            assert_eq!(new_comm.get_comm(r).0 - new_plain.get_comm(r).0, blinding);
+        }
+        let all_rows: Vec<usize> = (0..n).collect();
+        let before = new_comm.clone();
+        C::rerandomize(&srs, &mut new_comm, &all_rows, rng).expect("rerandomize should succeed");
+        for r in 0..n {
+            assert_ne!(new_comm.get_comm(r), before.get_comm(r));
+            let proof = C::open(&new_comm, &srs, r, point).expect("open should succeed");
+            assert!(C::verify(&vk, new_comm.get_comm(r), point, new_comm.get_poly(r).evaluate(&point), &proof).expect("verify should succeed"));
+        }
+
+        // the update proof works across the rerandomization
+        let update = ColumnUpdate::new(col_idx, old_col, new_col);
+        let proof = C::prove_update(&srs, &old_comm, &new_comm, &update, point, challenge).expect("prove update should succeed");
+        let old_comms: Vec<&Commitment<E>> = (0..n).map(|i| old_comm.get_comm(i)).collect();
+        let new_comms: Vec<&Commitment<E>> = (0..n).map(|i| new_comm.get_comm(i)).collect();
+        assert!(C::verify_update::<BLSFieldEncoder<F>>(&vk, &params, &old_comms, &new_comms, &proof, point, challenge).expect("verify update should succeed"));
+
+        // non-hiding commitments can't be rerandomized and an SRS without gamma powers can't hide
+        let mut plain_copy = plain.clone();
+        assert!(C::rerandomize(&srs, &mut plain_copy, &[0], rng).is_err());
+        let mut no_gamma = srs.clone();
+        no_gamma.powers_of_gamma_g.clear();
+        assert!(C::commit_hiding(&no_gamma, &matrix, hiding_bound, rng).is_err());
+    }
+
+    fn check_lagrange_commitments<Curve: Pairing>() {
+        type P<Curve> = KZG10LagrangePolyComm<Curve>;
+        type C<Curve> = MatrixPolyComm<Fr<Curve>, P<Curve>>;
+        let (k, n, m) = (4, 8, 6);
+        let params = Params{ k, n, m };
+        let mut matrix = Matrix::<Fr<Curve>>::new_random(params.clone());
+
+        let srs = C::<Curve>::setup(m, &SetupSource::InsecureTest).expect("setup should succeed");
+        let vk = srs.vk().unwrap();
+        let old_comm = C::<Curve>::commit(&srs, &matrix).expect("commit should succeed");
+
+        // the commitment is the MSM of the cells with the Lagrange basis
+        // and opening at the j-th domain point proves cell j
+        for r in 0..n {
+            let row = matrix.get_row(r).unwrap();
+            let expected = <Curve as Pairing>::G1::msm(&srs.lagrange_g[..m], &row).unwrap();
+            assert_eq!(old_comm.get_comm(r).0, expected.into_affine());
+            for (j, cell) in row.iter().enumerate() {
+                let point = srs.cell_point(j);
+                let proof = C::<Curve>::open(&old_comm, &srs, r, point).expect("open should succeed");
+                assert!(C::<Curve>::verify(&vk, old_comm.get_comm(r), point, *cell, &proof).expect("verify should succeed"));
+                assert!(!C::<Curve>::verify(&vk, old_comm.get_comm(r), point, *cell + Fr::<Curve>::from(1u64), &proof).expect("verify should succeed"));
+            }
+        }
+
+        // updating a column with cells that are zero or become zero keeps the rows consistent
+        let col_idx = m - 1;
+        let old_col = matrix.get_col(col_idx).unwrap();
+        let mut new_col: Vec<Fr<Curve>> = (0..n).map(|_| Fr::<Curve>::rand(&mut test_rng())).collect();
+        new_col[0] = Fr::<Curve>::zero();
+        for (r, cell) in new_col.iter().enumerate() {
+            matrix.set(r, col_idx, *cell).unwrap();
+        }
+        let mut new_comm = old_comm.clone();
+        C::<Curve>::update_commitments(&srs, &mut new_comm, col_idx, &old_col, &new_col).expect("update commitments should succeed");
+        let fresh = C::<Curve>::commit(&srs, &matrix).expect("commit should succeed");
+        for (r, cell) in new_col.iter().enumerate() {
+            assert_eq!(new_comm.get_comm(r), fresh.get_comm(r));
+            assert_eq!(new_comm.get_poly(r).polynomial(), fresh.get_poly(r).polynomial());
+            assert_eq!(new_comm.get_poly(r).evaluate(&srs.cell_point(col_idx)), *cell);
+        }
+
+        // batched updates of several columns
+        let updates: Vec<ColumnUpdate<Fr<Curve>>> = [0, 2].iter().map(|&c| {
+            let old = matrix.get_col(c).unwrap();
+            let new: Vec<Fr<Curve>> = (0..n).map(|_| Fr::<Curve>::rand(&mut test_rng())).collect();
+            for (r, cell) in new.iter().enumerate() {
+                matrix.set(r, c, *cell).unwrap();
+            }
+            ColumnUpdate::new(c, old, new)
+        }).collect();
+        C::<Curve>::update_commitments_batch(&srs, &mut new_comm, &updates).expect("update commitments batch should succeed");
+        let fresh = C::<Curve>::commit(&srs, &matrix).expect("commit should succeed");
+        for r in 0..n {
+            assert_eq!(new_comm.get_comm(r), fresh.get_comm(r));
+        }
+    }
+
+    #[test]
+    fn test_lagrange_commitments() {
+        check_lagrange_commitments::<E>();
+    }
+
+    #[test]
+    fn test_lagrange_commitments_bn254() {
+        check_lagrange_commitments::<ark_bn254::Bn254>();
+    }
+
+    #[test]
+    fn test_lagrange_update_proof() {
+        type P = KZG10LagrangePolyComm;
+        type C = MatrixPolyComm<F, P>;
+        let (k, n, m) = (4, 8, 8);
+        let params = Params{ k, n, m };
+        let (mut matrix, srs, old_comm) = commit_encoded::<P>(params.clone(), m);
+        let vk = srs.vk().unwrap();
+
+        let col_idx = 3;
+        let old_col = matrix.get_col(col_idx).unwrap();
+        matrix.update_col(col_idx, &(0..k).map(|_| F::rand(&mut test_rng())).collect::<Vec<_>>()).expect("update col should succeed");
+        BLSFieldEncoder::encode_col(&mut matrix, col_idx).expect("encode col failed");
+        let new_col = matrix.get_col(col_idx).unwrap();
+        let mut new_comm = old_comm.clone();
+        C::update_commitments(&srs, &mut new_comm, col_idx, &old_col, &new_col).expect("update commitments should succeed");
+
+        // the update proof checks the delta against the Lagrange basis of the updated cell
+        let rng = &mut test_rng();
+        let (point, challenge) = (F::rand(rng), F::rand(rng));
+        let update = ColumnUpdate::new(col_idx, old_col, new_col);
+        let proof = C::prove_update(&srs, &old_comm, &new_comm, &update, point, challenge).expect("prove update should succeed");
+        let old_comms: Vec<&Commitment<E>> = (0..n).map(|i| old_comm.get_comm(i)).collect();
+        let new_comms: Vec<&Commitment<E>> = (0..n).map(|i| new_comm.get_comm(i)).collect();
+        assert!(C::verify_update::<BLSFieldEncoder<F>>(&vk, &params, &old_comms, &new_comms, &proof, point, challenge).expect("verify update should succeed"));
+        let mut wrong_col = proof.clone();
+        wrong_col.col_idx = 2;
+        assert!(!C::verify_update::<BLSFieldEncoder<F>>(&vk, &params, &old_comms, &new_comms, &wrong_col, point, challenge).expect("verify update should succeed"));
+
+        // sampling a row at a cell point returns the stored cell
+        let point = srs.cell_point(col_idx);
+        let rows = [1, 4, 6];
+        let values: Vec<F> = rows.iter().map(|&r| matrix.get(r, col_idx).unwrap()).collect();
+        let proof = C::batch_open(&new_comm, &srs, &rows, point, challenge).expect("batch open should succeed");
+        let comms: Vec<&Commitment<E>> = rows.iter().map(|&r| new_comm.get_comm(r)).collect();
+        assert!(C::batch_verify(&vk, &comms, point, &values, challenge, &proof).expect("batch verify should succeed"));
+    }
+
+    #[test]
+    fn test_transcript() {
+        // prover and verifier appending the same data derive the same challenges
+        let mut prover = Transcript::new(b"test");
+        let mut verifier = Transcript::new(b"test");
+        for t in [&mut prover, &mut verifier] {
+            t.append_message(b"root", &[1u8; 32]);
+            t.append_u64(b"nonce", 5);
+        }
+        let point: F = prover.challenge_field(b"point");
+        assert_eq!(point, verifier.challenge_field::<F>(b"point"));
+        let rows = prover.challenge_indices(b"rows", 16, 16).expect("challenge indices should succeed");
+        assert_eq!(rows, verifier.challenge_indices(b"rows", 16, 16).expect("challenge indices should succeed"));
+        let mut sorted = rows.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..16).collect::<Vec<_>>());
+        // squeezing moves the state on
+        assert_ne!(prover.challenge_field::<F>(b"point"), point);
+        assert!(prover.challenge_indices(b"rows", 4, 5).is_err());
+
+        // any change in the public data or the labels changes the challenge
+        let derive = |root: &[u8], randomness: &[u8], nonce| Challenge::derive(root, randomness, nonce, 64, 8).unwrap();
+        let challenge = derive(&[1u8; 32], b"epoch 1", 0);
+        assert_eq!(challenge, derive(&[1u8; 32], b"epoch 1", 0));
+        assert_eq!(challenge.rows.len(), 8);
+        assert_ne!(challenge, derive(&[2u8; 32], b"epoch 1", 0));
+        assert_ne!(challenge, derive(&[1u8; 32], b"epoch 2", 0));
+        assert_ne!(challenge, derive(&[1u8; 32], b"epoch 1", 1));
+        let mut other = Transcript::new(b"other");
+        other.append_message(b"root", &[1u8; 32]);
+        other.append_u64(b"nonce", 5);
+        assert_ne!(other.challenge_field::<F>(b"point"), point);
+    }
+
+    #[test]
+    fn test_storage_proof() {
+        type P = KZG10PolyComm;
+        type C = MatrixPolyComm<F,P>;
+        let (k, n, m) = (4, 8, 8);
+        let params = Params{ k, n, m };
+        let data = Matrix::<F>::new_random(params);
+        let srs = C::setup(m, &SetupSource::InsecureTest).expect("setup should succeed");
+
+        let (client_end, sp_end) = channel();
+        let sp = StorageProvider::new(sp_end, srs.clone());
+        let handle = std::thread::spawn(move || sp.serve());
+        let mut client = Client::new(client_end);
+        client.upload(&srs, data).expect("upload should succeed");
+
+        // a third party checks the SP's proof with the commitments, the verifier key and the public randomness
+        let randomness = b"block 1234 randomness";
+        let (valid, response) = client.prove_storage(randomness, 1, 3).expect("prove storage should succeed");
+        assert!(valid);
+        let vk = get_vk(&srs).expect("get vk should succeed");
+        let comms = client.comms().to_vec();
+        assert!(verify_storage_proof(&vk, &comms, randomness, 1, 3, &response).expect("verify storage proof should succeed"));
+        // the proof is bound to the randomness, the nonce and the commitments
+        assert!(!verify_storage_proof(&vk, &comms, b"block 1235 randomness", 1, 3, &response).expect("verify storage proof should succeed"));
+        assert!(!verify_storage_proof(&vk, &comms, randomness, 2, 3, &response).expect("verify storage proof should succeed"));
+        let mut other_comms = comms.clone();
+        other_comms.swap(0, 1);
+        assert!(!verify_storage_proof(&vk, &other_comms, randomness, 1, 3, &response).expect("verify storage proof should succeed"));
+
+        // the challenge is the one both sides derive from the root
+        let challenge = Challenge::derive(&commitments_root(&comms).expect("commitments root should succeed"), randomness, 1, n, 3).expect("derive should succeed");
+        assert!(verify_response(&vk, &comms, &challenge, &response).expect("verify response should succeed"));
+
+        drop(client);
+        handle.join().unwrap().expect("serve should succeed");
+    }
+
+    fn check_open_cells<Curve: Pairing>() {
+        type P<Curve> = KZG10LagrangePolyComm<Curve>;
+        type C<Curve> = MatrixPolyComm<Fr<Curve>, P<Curve>>;
+        let (k, n, m) = (4, 8, 8);
+        let params = Params{ k, n, m };
+        let matrix = Matrix::<Fr<Curve>>::new_random(params);
+        let srs = C::<Curve>::setup(m, &SetupSource::InsecureTest).expect("setup should succeed");
+        let vk = srs.vk().unwrap();
+        let comm = C::<Curve>::commit(&srs, &matrix).expect("commit should succeed");
+        let comms: Vec<&Commitment<Curve>> = (0..n).map(|i| comm.get_comm(i)).collect();
+
+        // cells of the same row share one proof, repeated cells are opened once
+        let cells = [(2, 5), (6, 0), (2, 1), (2, 5), (6, 7), (0, 3)];
+        let proofs = C::<Curve>::open_cells(&comm, &srs, &cells).expect("open cells should succeed");
+        assert_eq!(proofs.iter().map(|p| (p.row, p.cols.clone())).collect::<Vec<_>>(),
+                   vec![(2, vec![5, 1]), (6, vec![0, 7]), (0, vec![3])]);
+        for proof in &proofs {
+            for (col, value) in proof.cols.iter().zip(&proof.values) {
+                assert_eq!(*value, matrix.get(proof.row, *col).unwrap());
+            }
+        }
+        assert!(C::<Curve>::verify_cells(&vk, &comms, &proofs).expect("verify cells should succeed"));
+
+        // wrong values, columns or rows are rejected
+        let mut wrong = proofs.clone();
+        wrong[0].values[1] += Fr::<Curve>::from(1u64);
+        assert!(!C::<Curve>::verify_cells(&vk, &comms, &wrong).expect("verify cells should succeed"));
+        let mut wrong = proofs.clone();
+        wrong[0].cols.swap(0, 1);
+        assert!(!C::<Curve>::verify_cells(&vk, &comms, &wrong).expect("verify cells should succeed"));
+        let mut wrong = proofs.clone();
+        wrong[1].row = 5;
+        assert!(!C::<Curve>::verify_cells(&vk, &comms, &wrong).expect("verify cells should succeed"));
+        let mut wrong = proofs.clone();
+        wrong[2].proof = proofs[1].proof.clone();
+        assert!(!C::<Curve>::verify_cells(&vk, &comms, &wrong).expect("verify cells should succeed"));
+        assert!(C::<Curve>::open_cells(&comm, &srs, &[(n, 0)]).is_err());
+    }
+
+    #[test]
+    fn test_open_cells() {
+        check_open_cells::<E>();
+    }
+
+    #[test]
+    fn test_open_cells_bn254() {
+        check_open_cells::<ark_bn254::Bn254>();
+    }
+
+    #[test]
+    fn test_open_multi() {
+        type P = KZG10PolyComm;
+        let rng = &mut StdRng::from_entropy();
+        let srs = P::setup(16, &SetupSource::InsecureTest).expect("setup should succeed");
+        let vk = get_vk(&srs).expect("get vk should succeed");
+        let coeffs: Vec<F> = (0..16).map(|_| F::rand(rng)).collect();
+        let points: Vec<F> = (0..5).map(|_| F::rand(rng)).collect();
+
+        // plain and hiding commitments in coefficient form open at arbitrary points
+        for comm in [P::commit(&srs, coeffs.clone()).expect("commit should succeed"), P::commit_hiding(&srs, coeffs.clone(), 1, rng).expect("commit hiding should succeed")] {
+            let (values, proof) = P::open_multi(&comm, &srs, &points).expect("open multi should succeed");
+            for (z, v) in points.iter().zip(&values) {
+                assert_eq!(comm.poly.evaluate(z), *v);
+            }
+            assert!(P::verify_multi(&vk, &comm.comm, &points, &values, &proof).expect("verify multi should succeed"));
+            let mut wrong = values.clone();
+            wrong[4] = F::rand(rng);
+            assert!(!P::verify_multi(&vk, &comm.comm, &points, &wrong, &proof).expect("verify multi should succeed"));
+            assert!(!P::verify_multi(&vk, &comm.comm, &points[..4], &values[..4], &proof).expect("verify multi should succeed"));
+        }
+
+        // the cells of a coefficient form row are not evaluations
+        assert!(P::cell_point(&vk, 0).is_err());
+        let comm = P::commit(&srs, coeffs).expect("commit should succeed");
+        assert!(P::open_multi(&comm, &srs, &[points[0], points[0]]).is_err());
+    }
+
+    #[test]
+    fn test_2d_commitments() {
+        type P = KZG10LagrangePolyComm;
+        type C2 = Matrix2DPolyComm<P>;
+        let (k, n, m) = (3, 8, 6);
+        let params = Params{ k, n, m };
+        let mut matrix = Matrix::<F>::new_random(params.clone());
+        BLSFieldEncoder::encode(&mut matrix).expect("encode failed");
+
+        let srs = C2::setup(&params, &SetupSource::InsecureTest).expect("setup should succeed");
+        let vk = C2::vk(&srs).unwrap();
+        let comm = C2::commit(&srs, &matrix).expect("commit should succeed");
+
+        // every cell, data and parity, opens to the same value in its row and its column
+        for r in 0..n {
+            for c in 0..m {
+                let proof = C2::open_cell(&srs, &comm, r, c).expect("open cell should succeed");
+                assert_eq!(proof.value, matrix.get(r, c).unwrap());
+                assert!(C2::verify_cell(&vk, comm.get_row_comm(r), comm.get_col_comm(c), &proof).expect("verify cell should succeed"));
+            }
+        }
+        let proof = C2::open_cell(&srs, &comm, 5, 2).expect("open cell should succeed");
+        let mut wrong = proof.clone();
+        wrong.value += F::from(1u64);
+        assert!(!C2::verify_cell(&vk, comm.get_row_comm(5), comm.get_col_comm(2), &wrong).expect("verify cell should succeed"));
+        assert!(!C2::verify_cell(&vk, comm.get_row_comm(5), comm.get_col_comm(3), &proof).expect("verify cell should succeed"));
+        assert!(!C2::verify_cell(&vk, comm.get_row_comm(4), comm.get_col_comm(2), &proof).expect("verify cell should succeed"));
+        let mut wrong = proof.clone();
+        wrong.row = 4;
+        assert!(!C2::verify_cell(&vk, comm.get_row_comm(4), comm.get_col_comm(2), &wrong).expect("verify cell should succeed"));
+
+        // columns that are not codewords can't be committed
+        let mut col = matrix.get_col(1).unwrap();
+        col[n - 1] += F::from(1u64);
+        assert!(C2::commit_col(&srs, &col).is_err());
+
+        // an update refreshes the rows and the one affected column
+        let col_idx = 4;
+        let old_col = matrix.get_col(col_idx).unwrap();
+        matrix.update_col(col_idx, &(0..k).map(|_| F::rand(&mut test_rng())).collect::<Vec<_>>()).expect("update col should succeed");
+        BLSFieldEncoder::encode_col(&mut matrix, col_idx).expect("encode col failed");
+        let new_col = matrix.get_col(col_idx).unwrap();
+        let mut new_comm = comm.clone();
+        C2::update_col(&srs, &mut new_comm, col_idx, &old_col, &new_col).expect("update col should succeed");
+        let fresh = C2::commit(&srs, &matrix).expect("commit should succeed");
+        for r in 0..n {
+            assert_eq!(new_comm.get_row_comm(r), fresh.get_row_comm(r));
+        }
+        for c in 0..m {
+            assert_eq!(new_comm.get_col_comm(c), fresh.get_col_comm(c));
+            assert_eq!(new_comm.get_col_comm(c) == comm.get_col_comm(c), c != col_idx);
+        }
+        let proof = C2::open_cell(&srs, &new_comm, n - 1, col_idx).expect("open cell should succeed");
+        assert!(C2::verify_cell(&vk, new_comm.get_row_comm(n - 1), new_comm.get_col_comm(col_idx), &proof).expect("verify cell should succeed"));
+    }
+
+    #[test]
+    fn test_versioned_store() {
+        type P = KZG10LagrangePolyComm;
+        let (k, n, m) = (4, 8, 8);
+        let (matrix, srs, _) = commit_encoded::<P>(Params{ k, n, m }, m);
+        let vk = P::vk(&srs).unwrap();
+
+        let mut store = VersionedStore::<F, P>::new(&srs, matrix.clone()).expect("new should succeed");
+        assert_eq!(store.version(), 0);
+        assert_eq!(store.log.root(0).unwrap(), commitments_root(store.log.comms(0).unwrap()).expect("commitments root should succeed"));
+
+        // three column updates, the second one rewrites column 1 again
+        let rng = &mut test_rng();
+        let mut snapshots = vec![matrix];
+        for col_idx in [1, 3, 1] {
+            let new_col: Vec<F> = (0..k).map(|_| F::rand(rng)).collect();
+            let version = store.update_col::<BLSFieldEncoder<F>>(&srs, col_idx, &new_col).expect("update col should succeed");
+            assert_eq!(version, snapshots.len());
+            let update = &store.updates[version - 1];
+            assert_eq!(update.col_idx, col_idx);
+            assert_eq!(update.new_col, store.matrix.get_col(col_idx).unwrap());
+            snapshots.push(store.matrix.clone());
+        }
+        let roots: Vec<_> = (0..=3).map(|v| store.log.root(v).unwrap()).collect();
+        for v in 1..=3 {
+            assert!(!roots[..v].contains(&roots[v]), "version {} has the root of an earlier version", v);
+        }
+
+        // a proof from the state of version 1 verifies against version 1 only
+        let (old_matrix, old_comm) = store.checkout(&srs, 1).expect("checkout should succeed");
+        assert_eq!(old_matrix.elms, snapshots[1].elms);
+        assert_eq!(store.version(), 3);
+        let cells = [(0, 1), (5, 1), (6, 3)];
+        let proofs = MatrixPolyComm::<F, P>::open_cells(&old_comm, &srs, &cells).expect("open cells should succeed");
+        assert!(store.log.verify_cells::<F, P>(&vk, 1, &proofs).expect("verify cells should succeed"));
+        assert!(!store.log.verify_cells::<F, P>(&vk, 3, &proofs).expect("verify cells should succeed"));
+        let latest = MatrixPolyComm::<F, P>::open_cells(&store.comm, &srs, &cells).expect("open cells should succeed");
+        assert!(store.log.verify_cells::<F, P>(&vk, 3, &latest).expect("verify cells should succeed"));
+        assert!(store.log.verify_cells::<F, P>(&vk, 4, &latest).is_err());
+
+        // a rollback that doesn't reach the recorded commitments leaves the store unchanged
+        let mut tampered = store.updates[2].clone();
+        tampered.old_col[0] += F::from(1u8);
+        let honest = std::mem::replace(&mut store.updates[2], tampered);
+        assert!(store.rollback(&srs, 1).is_err());
+        assert_eq!(store.version(), 3);
+        assert_eq!(store.matrix.elms, snapshots[3].elms);
+        store.updates[2] = honest;
+
+        // roll back to version 1: the matrix and commitments are those of a fresh commit
+        store.rollback(&srs, 1).expect("rollback should succeed");
+        assert_eq!(store.version(), 1);
+        assert_eq!(store.matrix.elms, snapshots[1].elms);
+        let fresh = MatrixPolyComm::<F, P>::commit(&srs, &store.matrix).expect("commit should succeed");
+        for r in 0..n {
+            assert_eq!(store.comm.get_comm(r), fresh.get_comm(r));
+        }
+        assert!(store.log.get(2).is_err());
+        assert_eq!(store.updates.len(), 1);
+        assert!(store.rollback(&srs, 2).is_err());
+
+        // the history continues from version 1, and goes back to the original data
+        let new_col: Vec<F> = (0..k).map(|_| F::rand(rng)).collect();
+        assert_eq!(store.update_col::<BLSFieldEncoder<F>>(&srs, 5, &new_col).expect("update col should succeed"), 2);
+        store.rollback(&srs, 0).expect("rollback should succeed");
+        assert_eq!(store.matrix.elms, snapshots[0].elms);
+        assert_eq!(store.log.root(0).unwrap(), roots[0]);
+    }
 }
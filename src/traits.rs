@@ -1,6 +1,8 @@
 use std::marker::PhantomData;
 use anyhow::Result;
+use ark_std::rand::{CryptoRng, RngCore};
 use crate::byte_data::Params;
+use crate::srs::SetupSource;
 
 pub trait DataMatrix<T>{
     type Params;
@@ -10,6 +12,12 @@ pub trait DataMatrix<T>{
     fn get_col(&self, c: usize) -> Result<Vec<T>>;
     fn set(&mut self, r: usize, c: usize, elem: T) -> Result<()>;
     fn update_col(&mut self, c: usize, new_col: &[T]) -> Result<()>;
+    /// append columns of `k` data cells after the last column, their parity cells are zero until encoded
+    fn append_cols(&mut self, new_cols: &[Vec<T>]) -> Result<()>;
+    /// keep only the first `m` columns
+    fn truncate_cols(&mut self, m: usize) -> Result<()>;
+    /// remove column `c`, the columns after it move one to the left
+    fn delete_col(&mut self, c: usize) -> Result<()>;
     fn pretty_print(&self);
 }
 
@@ -23,8 +31,13 @@ pub trait Encoder<T>{
     fn encode(data: &mut Self::DataMatrix<T>) -> Result<()>;
     /// encode a single column in place
     fn encode_col(data: &mut Self::DataMatrix<T>, c: usize) -> Result<()>;
+    /// update the parity of column `c` in place after the data cells at `changed_rows` changed from `old_vals`
+    /// (the matrix already holds the new values), only the contribution of the deltas is added to the parity
+    fn update_parity(data: &mut Self::DataMatrix<T>, c: usize, changed_rows: &[usize], old_vals: &[T]) -> Result<()>;
     /// reconstruct in place
     fn reconstruct(params: Params, matrix_opts: &mut Vec<Option<Vec<T>>>) -> Result<()>;
+    /// check that the column `col` (data then parity cells) is a codeword
+    fn is_codeword(params: Params, col: &[T]) -> Result<bool>;
 }
 
 pub trait CommitOutputTrait {
@@ -52,16 +65,29 @@ pub trait CommitOutputTrait {
 // }
 
 /// Polynomial Commitment scheme (e.g. KZG) trait
+/// the SRS and commitment outputs are shared across threads with the `parallel` feature
 pub trait PolyCommScheme<F>{
-    type SRS;
+    type SRS: Sync;
     type VK;
-    type CommitOutput: CommitOutputTrait;
+    type CommitOutput: CommitOutputTrait + Send + Sync;
     type Comm;
-    type Proof;
+    type Proof: Send;
+    /// proof of the evaluations at several points
+    type MultiProof: Send;
 
-    fn setup(degree: usize) -> Result<Self::SRS>;
+    fn setup(degree: usize, source: &SetupSource) -> Result<Self::SRS>;
+    fn vk(srs: &Self::SRS) -> Result<Self::VK>;
+    /// the maximum number of cells in a committed input, i.e. the row length the SRS supports
+    fn max_cells(srs: &Self::SRS) -> usize;
     fn commit(srs: &Self::SRS, input:Vec<F>) -> Result<Self::CommitOutput>;
+    /// hiding commitment that stays hiding for up to `hiding_bound` openings, the blinding is sampled from `rng`
+    fn commit_hiding<R: RngCore + CryptoRng>(srs: &Self::SRS, input: Vec<F>, hiding_bound: usize, rng: &mut R) -> Result<Self::CommitOutput>;
+    /// replace the blinding of a hiding commitment with a fresh one, so an updated commitment
+    /// can't be linked to the previous one
+    fn rerandomize<R: RngCore + CryptoRng>(srs: &Self::SRS, comm: &mut Self::CommitOutput, rng: &mut R) -> Result<()>;
     fn update_commitment(srs: &Self::SRS, original_comm: &mut Self::CommitOutput, original_cell: F, new_cell:F, index: usize) -> Result<()>;
+    /// update several cells at once, each given as `(index, original_cell, new_cell)`
+    fn update_commitment_batch(srs: &Self::SRS, original_comm: &mut Self::CommitOutput, cells: &[(usize, F, F)]) -> Result<()>;
     fn open(
         comm: &Self::CommitOutput,
         srs: &Self::SRS,
@@ -74,6 +100,42 @@ pub trait PolyCommScheme<F>{
         value: F,
         proof: &Self::Proof,
     ) -> Result<bool>;
+    /// evaluation at `point` of the basis polynomial multiplying the cell at `index`,
+    /// i.e. the polynomial whose commitment is added when that cell grows by one
+    fn cell_basis(vk: &Self::VK, index: usize, point: F) -> Result<F>;
+    /// the point at which the committed polynomial evaluates to the cell at `index`,
+    /// fails if the cells are not evaluations of the polynomial
+    fn cell_point(vk: &Self::VK, index: usize) -> Result<F>;
+    /// open the committed polynomial at the distinct `points` with a single proof, returns the evaluations
+    fn open_multi(
+        comm: &Self::CommitOutput,
+        srs: &Self::SRS,
+        points: &[F],
+    ) -> Result<(Vec<F>, Self::MultiProof)>;
+    /// verify that the committed polynomial evaluates to `values` at `points`
+    fn verify_multi(
+        vk:   &Self::VK,
+        comm: &Self::Comm,
+        points: &[F],
+        values: &[F],
+        proof: &Self::MultiProof,
+    ) -> Result<bool>;
+    /// open the linear combination `sum_i coeffs[i] * p_i` of the committed polynomials at `point`
+    fn open_combination(
+        comms: &[&Self::CommitOutput],
+        coeffs: &[F],
+        srs: &Self::SRS,
+        point: F,
+    ) -> Result<Self::Proof>;
+    /// verify that the linear combination `sum_i coeffs[i] * comm_i` opens to `value` at `point`
+    fn verify_combination(
+        vk:   &Self::VK,
+        comms: &[&Self::Comm],
+        coeffs: &[F],
+        point: F,
+        value: F,
+        proof: &Self::Proof,
+    ) -> Result<bool>;
 }
 
 /// Polynomial Commitment scheme for a field Matrix
@@ -82,8 +144,12 @@ pub trait PolyCommScheme<F>{
 pub trait MatrixPolyCommScheme<F, P:PolyCommScheme<F>>{
     type FieldMatrix: DataMatrix<F>;
 
-    fn setup(m: usize) -> Result<P::SRS>;
+    fn setup(m: usize, source: &SetupSource) -> Result<P::SRS>;
     fn commit(srs: &P::SRS, matrix:&Self::FieldMatrix) -> Result<MatrixCommitOutput<F, P>>;
+    /// hiding commitments to the rows, see [`PolyCommScheme::commit_hiding`]
+    fn commit_hiding<R: RngCore + CryptoRng>(srs: &P::SRS, matrix: &Self::FieldMatrix, hiding_bound: usize, rng: &mut R) -> Result<MatrixCommitOutput<F, P>>;
+    /// rerandomize the given rows, e.g. those changed by an update
+    fn rerandomize<R: RngCore + CryptoRng>(srs: &P::SRS, comm: &mut MatrixCommitOutput<F, P>, rows: &[usize], rng: &mut R) -> Result<()>;
     fn update_commitments(
         srs: &P::SRS,
         comm: &mut MatrixCommitOutput<F, P>,
@@ -91,6 +157,35 @@ pub trait MatrixPolyCommScheme<F, P:PolyCommScheme<F>>{
         old_col: &[F],
         new_col: &[F],
     ) -> Result<()>;
+    /// update the row commitments after updating several columns at once
+    fn update_commitments_batch(
+        srs: &P::SRS,
+        comm: &mut MatrixCommitOutput<F, P>,
+        updates: &[ColumnUpdate<F>],
+    ) -> Result<()>;
+    /// update the row commitments after appending the encoded `new_cols` (`n` cells each) from column `first_col` on,
+    /// the new cells use the next powers of the SRS, fails if the rows outgrow the SRS
+    fn append_commitments(
+        srs: &P::SRS,
+        comm: &mut MatrixCommitOutput<F, P>,
+        first_col: usize,
+        new_cols: &[Vec<F>],
+    ) -> Result<()>;
+    /// update the row commitments after truncating the columns from `first_col` on, `old_cols` are the removed columns
+    fn truncate_commitments(
+        srs: &P::SRS,
+        comm: &mut MatrixCommitOutput<F, P>,
+        first_col: usize,
+        old_cols: &[Vec<F>],
+    ) -> Result<()>;
+    /// update the row commitments after deleting column `col_idx`,
+    /// `old_cols` are the columns from `col_idx` to the last one before the delete
+    fn delete_col_commitments(
+        srs: &P::SRS,
+        comm: &mut MatrixCommitOutput<F, P>,
+        col_idx: usize,
+        old_cols: &[Vec<F>],
+    ) -> Result<()>;
     fn open(
         comm: &MatrixCommitOutput<F, P>,
         srs: &P::SRS,
@@ -104,6 +199,135 @@ pub trait MatrixPolyCommScheme<F, P:PolyCommScheme<F>>{
         value: F,
         proof: &P::Proof,
     ) -> Result<bool>;
+    /// open several rows at the same `point` with a single aggregated proof,
+    /// the rows are combined with the powers of the random `challenge`
+    fn batch_open(
+        comm: &MatrixCommitOutput<F, P>,
+        srs: &P::SRS,
+        rows: &[usize],
+        point: F,
+        challenge: F,
+    ) -> Result<P::Proof>;
+    /// verify an aggregated proof for the row commitments `comms` opening to `values` at `point`
+    fn batch_verify(
+        vk:   &P::VK,
+        comms: &[&P::Comm],
+        point: F,
+        values: &[F],
+        challenge: F,
+        proof: &P::Proof,
+    ) -> Result<bool>;
+    /// open the cells at the `(row, col)` positions at their domain points,
+    /// the cells of each row are opened with a single multi-point proof
+    fn open_cells(
+        comm: &MatrixCommitOutput<F, P>,
+        srs: &P::SRS,
+        cells: &[(usize, usize)],
+    ) -> Result<Vec<CellProof<F, P>>>;
+    /// verify the cell proofs against the row commitments `comms` (all rows, indexed by row)
+    fn verify_cells(
+        vk:   &P::VK,
+        comms: &[&P::Comm],
+        proofs: &[CellProof<F, P>],
+    ) -> Result<bool>;
+    /// prove that `new_comm` is `old_comm` updated by the (encoded) column `update`,
+    /// `point` and `challenge` must be chosen by the verifier after seeing the new commitments
+    fn prove_update(
+        srs: &P::SRS,
+        old_comm: &MatrixCommitOutput<F, P>,
+        new_comm: &MatrixCommitOutput<F, P>,
+        update: &ColumnUpdate<F>,
+        point: F,
+        challenge: F,
+    ) -> Result<UpdateProof<F, P>>;
+    /// verify an update proof against the old and new row commitments,
+    /// also checks with the encoder `Enc` that the column delta (hence the new column) is a codeword
+    fn verify_update<Enc: Encoder<F>>(
+        vk:   &P::VK,
+        params: &Params,
+        old_comms: &[&P::Comm],
+        new_comms: &[&P::Comm],
+        proof: &UpdateProof<F, P>,
+        point: F,
+        challenge: F,
+    ) -> Result<bool>;
+}
+
+/// Proof of a column update: the row commitments changed exactly by the committed column `delta`,
+/// `proof` opens `sum_r challenge^r * (new_row_r - old_row_r)` at the verifier's point
+pub struct UpdateProof<F, P: PolyCommScheme<F>> {
+    pub col_idx: usize,
+    /// the change of every cell (data and parity) in the column
+    pub delta: Vec<F>,
+    pub proof: P::Proof,
+}
+
+impl<F: Clone, P: PolyCommScheme<F>> Clone for UpdateProof<F, P> where P::Proof: Clone {
+    fn clone(&self) -> Self {
+        Self{
+            col_idx: self.col_idx,
+            delta: self.delta.clone(),
+            proof: self.proof.clone(),
+        }
+    }
+}
+
+impl<F: std::fmt::Debug, P: PolyCommScheme<F>> std::fmt::Debug for UpdateProof<F, P> where P::Proof: std::fmt::Debug {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UpdateProof")
+            .field("col_idx", &self.col_idx)
+            .field("delta", &self.delta)
+            .field("proof", &self.proof)
+            .finish()
+    }
+}
+
+/// the opened cells of a row: their columns and values with one proof for all of them
+pub struct CellProof<F, P: PolyCommScheme<F>> {
+    pub row: usize,
+    pub cols: Vec<usize>,
+    pub values: Vec<F>,
+    pub proof: P::MultiProof,
+}
+
+impl<F: Clone, P: PolyCommScheme<F>> Clone for CellProof<F, P> where P::MultiProof: Clone {
+    fn clone(&self) -> Self {
+        Self{
+            row: self.row,
+            cols: self.cols.clone(),
+            values: self.values.clone(),
+            proof: self.proof.clone(),
+        }
+    }
+}
+
+impl<F: std::fmt::Debug, P: PolyCommScheme<F>> std::fmt::Debug for CellProof<F, P> where P::MultiProof: std::fmt::Debug {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CellProof")
+            .field("row", &self.row)
+            .field("cols", &self.cols)
+            .field("values", &self.values)
+            .field("proof", &self.proof)
+            .finish()
+    }
+}
+
+/// an update of a single column: the column index with the old and new (encoded) columns
+#[derive(Clone, Debug)]
+pub struct ColumnUpdate<F> {
+    pub col_idx: usize,
+    pub old_col: Vec<F>,
+    pub new_col: Vec<F>,
+}
+
+impl<F> ColumnUpdate<F> {
+    pub fn new(col_idx: usize, old_col: Vec<F>, new_col: Vec<F>) -> Self {
+        Self{
+            col_idx,
+            old_col,
+            new_col,
+        }
+    }
 }
 
 pub struct MatrixCommitOutput<F, P: PolyCommScheme<F>> {
@@ -111,25 +335,31 @@ pub struct MatrixCommitOutput<F, P: PolyCommScheme<F>> {
     phantom_data: PhantomData<F>
 }
 
+impl<F, P: PolyCommScheme<F>> Clone for MatrixCommitOutput<F, P> where P::CommitOutput: Clone {
+    fn clone(&self) -> Self {
+        Self::new(self.comm_output.clone())
+    }
+}
+
 impl<F, P: PolyCommScheme<F>> MatrixCommitOutput<F, P> {
     pub fn new(
         comm_output: Vec<P::CommitOutput>
     ) -> Self{
         Self{
             comm_output,
-            phantom_data:PhantomData::default(),
+            phantom_data:PhantomData,
         }
     }
 
     pub fn get_poly(&self, idx: usize) -> &<P::CommitOutput as CommitOutputTrait>::Poly{
-        &self.comm_output[idx].get_poly()
+        self.comm_output[idx].get_poly()
     }
 
     pub fn get_comm(&self, idx: usize) -> &<P::CommitOutput as CommitOutputTrait>::Comm{
-        &self.comm_output[idx].get_comm()
+        self.comm_output[idx].get_comm()
     }
 
     pub fn get_rand(&self, idx: usize) -> &<P::CommitOutput as CommitOutputTrait>::Rand{
-        &self.comm_output[idx].get_rand()
+        self.comm_output[idx].get_rand()
     }
 }
\ No newline at end of file
@@ -0,0 +1,105 @@
+use anyhow::{anyhow, Result};
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use sha2::{Digest, Sha256};
+
+/// Fiat–Shamir transcript in the style of Merlin, built on a Sha256 hash chain:
+/// every labelled message is absorbed into the 32 byte state and challenges are squeezed from it,
+/// so the prover and any verifier appending the same public data derive the same challenges
+#[derive(Clone, Debug)]
+pub struct Transcript {
+    state: [u8; 32],
+}
+
+impl Transcript {
+    /// a new transcript, `label` separates the protocols using it
+    pub fn new(label: &[u8]) -> Self {
+        let mut transcript = Self{ state: [0u8; 32] };
+        transcript.append_message(b"dom-sep", label);
+        transcript
+    }
+
+    pub fn append_message(&mut self, label: &[u8], message: &[u8]) {
+        self.ratchet(b"absorb", label, message);
+    }
+
+    pub fn append_u64(&mut self, label: &[u8], value: u64) {
+        self.append_message(label, &value.to_le_bytes());
+    }
+
+    /// absorb the compressed serialization of `value`, e.g. a commitment
+    pub fn append_serializable<T: CanonicalSerialize>(&mut self, label: &[u8], value: &T) -> Result<()> {
+        let mut bytes = vec![];
+        value.serialize_compressed(&mut bytes)?;
+        self.append_message(label, &bytes);
+        Ok(())
+    }
+
+    /// fill `out` with challenge bytes, the state moves on so the next challenge is independent
+    pub fn challenge_bytes(&mut self, label: &[u8], out: &mut [u8]) {
+        for (i, chunk) in out.chunks_mut(32).enumerate() {
+            let mut hasher = Sha256::new();
+            hasher.update(self.state);
+            hasher.update(b"squeeze");
+            update_framed(&mut hasher, label);
+            hasher.update((i as u64).to_le_bytes());
+            let block: [u8; 32] = hasher.finalize().into();
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+        self.ratchet(b"squeezed", label, &(out.len() as u64).to_le_bytes());
+    }
+
+    /// a field element challenge, reduced from 64 bytes so the bias is negligible
+    pub fn challenge_field<F: PrimeField>(&mut self, label: &[u8]) -> F {
+        let mut bytes = [0u8; 64];
+        self.challenge_bytes(label, &mut bytes);
+        F::from_le_bytes_mod_order(&bytes)
+    }
+
+    /// a uniform index in `0..n`, by rejection sampling to avoid the modulo bias
+    pub fn challenge_index(&mut self, label: &[u8], n: usize) -> Result<usize> {
+        if n == 0 {
+            return Err(anyhow!("cannot sample an index out of 0"));
+        }
+        let n = n as u64;
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let mut bytes = [0u8; 8];
+            self.challenge_bytes(label, &mut bytes);
+            let value = u64::from_le_bytes(bytes);
+            if value < zone {
+                return Ok((value % n) as usize);
+            }
+        }
+    }
+
+    /// `count` distinct indices in `0..n`, in the order they were drawn
+    pub fn challenge_indices(&mut self, label: &[u8], n: usize, count: usize) -> Result<Vec<usize>> {
+        if count > n {
+            return Err(anyhow!("cannot sample {} distinct indices out of {}", count, n));
+        }
+        let mut indices = Vec::with_capacity(count);
+        while indices.len() < count {
+            let index = self.challenge_index(label, n)?;
+            if !indices.contains(&index) {
+                indices.push(index);
+            }
+        }
+        Ok(indices)
+    }
+
+    fn ratchet(&mut self, op: &[u8], label: &[u8], message: &[u8]) {
+        let mut hasher = Sha256::new();
+        hasher.update(self.state);
+        hasher.update(op);
+        update_framed(&mut hasher, label);
+        update_framed(&mut hasher, message);
+        self.state = hasher.finalize().into();
+    }
+}
+
+/// length prefixed so that the boundaries between label and message are unambiguous
+fn update_framed(hasher: &mut Sha256, bytes: &[u8]) {
+    hasher.update((bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+}
@@ -0,0 +1,184 @@
+use anyhow::{anyhow, Result};
+use ark_ff::Field;
+use ark_serialize::CanonicalSerialize;
+use crate::field_matrix::Matrix;
+use crate::encoder::changed_cells;
+use crate::matrix_commit::MatrixPolyComm;
+use crate::merkle::{MerkleTree, Sha256Hasher};
+use crate::traits::{CellProof, ColumnUpdate, CommitOutputTrait, DataMatrix, Encoder, MatrixCommitOutput, MatrixPolyCommScheme, PolyCommScheme};
+
+/// one version of the dataset
+#[derive(Clone, Debug)]
+pub struct VersionEntry<C> {
+    /// the row commitments of this version
+    pub comms: Vec<C>,
+    /// the Merkle root of `comms`
+    pub root: [u8; 32],
+}
+
+/// the public history of a dataset: version 0 is the initial commit and version `v`
+/// the state after the first `v` column updates.
+/// it holds no data, so a verifier can keep it to check proofs against any version
+#[derive(Clone, Debug)]
+pub struct VersionLog<C> {
+    entries: Vec<VersionEntry<C>>,
+}
+
+impl<C: CanonicalSerialize> VersionLog<C> {
+    pub fn new(comms: Vec<C>) -> Result<Self> {
+        let root = comms_root(&comms)?;
+        Ok(Self{
+            entries: vec![VersionEntry{ comms, root }],
+        })
+    }
+
+    /// the latest version
+    pub fn version(&self) -> usize {
+        self.entries.len() - 1
+    }
+
+    pub fn get(&self, version: usize) -> Result<&VersionEntry<C>> {
+        self.entries.get(version)
+            .ok_or(anyhow!("version {} doesn't exist, the latest is {}", version, self.version()))
+    }
+
+    pub fn comms(&self, version: usize) -> Result<&[C]> {
+        Ok(&self.get(version)?.comms)
+    }
+
+    pub fn root(&self, version: usize) -> Result<[u8; 32]> {
+        Ok(self.get(version)?.root)
+    }
+
+    /// record the commitments after a column update as a new version
+    pub fn push(&mut self, comms: Vec<C>) -> Result<usize> {
+        let root = comms_root(&comms)?;
+        self.entries.push(VersionEntry{ comms, root });
+        Ok(self.version())
+    }
+
+    /// verify opened cells against the commitments of `version`
+    pub fn verify_cells<F: Field, P: PolyCommScheme<F, Comm = C>>(
+        &self,
+        vk: &P::VK,
+        version: usize,
+        proofs: &[CellProof<F, P>],
+    ) -> Result<bool> {
+        let comms: Vec<&C> = self.comms(version)?.iter().collect();
+        MatrixPolyComm::<F, P>::verify_cells(vk, &comms, proofs)
+    }
+}
+
+/// an encoded matrix with its row commitments and their version history.
+/// the commitments of each version are recorded in the log and the column deltas in the store,
+/// so the matrix and commitments can be rolled back by applying the deltas in reverse
+pub struct VersionedStore<F: Field, P: PolyCommScheme<F>> {
+    pub matrix: Matrix<F>,
+    pub comm: MatrixCommitOutput<F, P>,
+    pub log: VersionLog<P::Comm>,
+    /// the column update that produced each version, `updates[v - 1]` for version `v`
+    pub updates: Vec<ColumnUpdate<F>>,
+}
+
+impl<F: Field, P: PolyCommScheme<F>> VersionedStore<F, P>
+where
+    P::CommitOutput: CommitOutputTrait<Comm = P::Comm>,
+    P::Comm: Clone + PartialEq + CanonicalSerialize,
+{
+    /// commit to the encoded matrix as version 0
+    pub fn new(srs: &P::SRS, matrix: Matrix<F>) -> Result<Self> {
+        let comm = MatrixPolyComm::<F, P>::commit(srs, &matrix)?;
+        let log = VersionLog::new(row_comms(&comm))?;
+        Ok(Self{
+            matrix,
+            comm,
+            log,
+            updates: vec![],
+        })
+    }
+
+    pub fn version(&self) -> usize {
+        self.log.version()
+    }
+
+    /// replace the `k` data cells of column `col_idx`, update its parity from the changed cells
+    /// and update the commitments, returns the new version
+    pub fn update_col<Enc: Encoder<F, DataMatrix<F> = Matrix<F>>>(
+        &mut self,
+        srs: &P::SRS,
+        col_idx: usize,
+        new_col: &[F],
+    ) -> Result<usize> {
+        let old_col = self.matrix.get_col(col_idx)?;
+        self.matrix.update_col(col_idx, new_col)?;
+        let (changed_rows, old_vals) = changed_cells(&old_col, new_col);
+        Enc::update_parity(&mut self.matrix, col_idx, &changed_rows, &old_vals)?;
+        let encoded_new_col = self.matrix.get_col(col_idx)?;
+        MatrixPolyComm::<F, P>::update_commitments(srs, &mut self.comm, col_idx, &old_col, &encoded_new_col)?;
+        self.updates.push(ColumnUpdate::new(col_idx, old_col, encoded_new_col));
+        self.log.push(row_comms(&self.comm))
+    }
+
+    /// the matrix and commitments of an earlier `version`, the store is left unchanged
+    pub fn checkout(&self, srs: &P::SRS, version: usize) -> Result<(Matrix<F>, MatrixCommitOutput<F, P>)>
+    where
+        P::CommitOutput: Clone,
+    {
+        let expected = self.log.comms(version)?;
+        let mut matrix = self.matrix.clone();
+        let mut comm = self.comm.clone();
+        revert(srs, &mut matrix, &mut comm, &self.updates[version..], expected)?;
+        Ok((matrix, comm))
+    }
+
+    /// roll the matrix, commitments and log back to `version`, the later versions are dropped.
+    /// the store is left unchanged if the rollback fails
+    pub fn rollback(&mut self, srs: &P::SRS, version: usize) -> Result<()>
+    where
+        P::CommitOutput: Clone,
+    {
+        let (matrix, comm) = self.checkout(srs, version)?;
+        self.matrix = matrix;
+        self.comm = comm;
+        self.log.entries.truncate(version + 1);
+        self.updates.truncate(version);
+        Ok(())
+    }
+}
+
+/// undo `updates` from the latest to the earliest and check the commitments are back to `expected`
+fn revert<F: Field, P: PolyCommScheme<F>>(
+    srs: &P::SRS,
+    matrix: &mut Matrix<F>,
+    comm: &mut MatrixCommitOutput<F, P>,
+    updates: &[ColumnUpdate<F>],
+    expected: &[P::Comm],
+) -> Result<()>
+where
+    P::CommitOutput: CommitOutputTrait<Comm = P::Comm>,
+    P::Comm: Clone + PartialEq + CanonicalSerialize,
+{
+    for update in updates.iter().rev() {
+        for (r, cell) in update.old_col.iter().enumerate() {
+            matrix.set(r, update.col_idx, *cell)?;
+        }
+        MatrixPolyComm::<F, P>::update_commitments(srs, comm, update.col_idx, &update.new_col, &update.old_col)?;
+    }
+    if row_comms(comm) != expected {
+        return Err(anyhow!("undoing {} updates doesn't give the recorded commitments", updates.len()));
+    }
+    Ok(())
+}
+
+fn row_comms<F, P: PolyCommScheme<F>>(comm: &MatrixCommitOutput<F, P>) -> Vec<P::Comm>
+where
+    P::CommitOutput: CommitOutputTrait<Comm = P::Comm>,
+    P::Comm: Clone,
+{
+    comm.comm_output.iter().map(|c| c.get_comm().clone()).collect()
+}
+
+/// the Merkle root of the row commitments of a version, as in `protocol::commitments_root`
+fn comms_root<C: CanonicalSerialize>(comms: &[C]) -> Result<[u8; 32]> {
+    Ok(MerkleTree::from_commitments(Sha256Hasher, comms)?.root())
+}